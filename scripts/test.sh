@@ -83,8 +83,9 @@ fi
 
 # Test 6: Test basic MCP communication (if possible)
 echo -e "${YELLOW}Test 6: Testing basic MCP communication...${NC}"
+init_request='{"jsonrpc":"2.0","id":"init","method":"initialize","params":{"protocolVersion":"2025-06-18","capabilities":{}}}'
 test_request='{"jsonrpc":"2.0","id":"test","method":"tools/list","params":null}'
-if timeout 5 bash -c "echo '$test_request' | docker exec -i '$CONTAINER_NAME' /app/jup-mpc" 2>/dev/null | grep -q "tools"; then
+if timeout 5 bash -c "printf '%s\n%s\n' '$init_request' '$test_request' | docker exec -i '$CONTAINER_NAME' /app/jup-mpc" 2>/dev/null | grep -q "tools"; then
     echo -e "${GREEN}✅ MCP communication successful${NC}"
 else
     echo -e "${YELLOW}⚠️  MCP communication test inconclusive (this may be normal for stdio-based servers)${NC}"
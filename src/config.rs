@@ -1,5 +1,8 @@
 use crate::error::{JupiterMcpError, Result};
 use solana_sdk::commitment_config::CommitmentConfig;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Debug, Clone)]
 pub enum SolanaNetwork {
@@ -41,12 +44,142 @@ impl std::str::FromStr for SolanaNetwork {
     }
 }
 
+/// Which Jupiter API generation the swap tools talk to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JupiterBackend {
+    /// Ultra `/order` + `/execute`; Jupiter builds and lands the transaction
+    Ultra,
+    /// Legacy v6 `/quote` + `/swap`; the server broadcasts over RPC
+    SwapV6,
+}
+
+impl std::str::FromStr for JupiterBackend {
+    type Err = JupiterMcpError;
+    
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "ultra" => Ok(JupiterBackend::Ultra),
+            "v6" | "swap-v6" => Ok(JupiterBackend::SwapV6),
+            _ => Err(JupiterMcpError::Environment(
+                format!("Invalid Jupiter backend: {}. Use 'ultra' or 'v6'", s)
+            )),
+        }
+    }
+}
+
+/// How MCP clients connect to the server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transport {
+    Stdio,
+    /// Streamable HTTP: POST for requests, SSE for server-initiated messages
+    Http,
+}
+
+impl std::str::FromStr for Transport {
+    type Err = JupiterMcpError;
+    
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "stdio" => Ok(Transport::Stdio),
+            "http" | "streamable-http" => Ok(Transport::Http),
+            _ => Err(JupiterMcpError::Environment(
+                format!("Invalid transport: {}. Use 'stdio' or 'http'", s)
+            )),
+        }
+    }
+}
+
+/// Jupiter API endpoints and HTTP client settings
+#[derive(Debug, Clone)]
+pub struct JupiterConfig {
+    pub backend: JupiterBackend,
+    pub ultra_api_url: String,
+    pub swap_api_url: String,
+    /// Token API serving the verified token list
+    pub token_api_url: String,
+    /// Price API used to value holdings in USD
+    pub price_api_url: String,
+    pub api_key: Option<String>,
+    pub timeout: Duration,
+}
+
+impl Default for JupiterConfig {
+    fn default() -> Self {
+        Self {
+            backend: JupiterBackend::Ultra,
+            ultra_api_url: "https://ultra-api.jup.ag".to_string(),
+            swap_api_url: "https://quote-api.jup.ag/v6".to_string(),
+            token_api_url: "https://lite-api.jup.ag/tokens/v1".to_string(),
+            price_api_url: "https://lite-api.jup.ag/price/v3".to_string(),
+            api_key: None,
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+impl JupiterConfig {
+    fn from_env() -> Result<Self> {
+        let defaults = Self::default();
+        
+        let backend: JupiterBackend = std::env::var("JUPITER_BACKEND")
+            .unwrap_or_else(|_| "ultra".to_string())
+            .parse()?;
+        
+        let timeout = match std::env::var("JUPITER_TIMEOUT_SECS") {
+            Ok(secs) => Duration::from_secs(secs.parse().map_err(|_| JupiterMcpError::Environment(
+                format!("Invalid JUPITER_TIMEOUT_SECS: {}", secs)
+            ))?),
+            Err(_) => defaults.timeout,
+        };
+        
+        Ok(Self {
+            backend,
+            ultra_api_url: std::env::var("JUPITER_ULTRA_API_URL").unwrap_or(defaults.ultra_api_url),
+            swap_api_url: std::env::var("JUPITER_SWAP_API_URL").unwrap_or(defaults.swap_api_url),
+            token_api_url: std::env::var("JUPITER_TOKEN_API_URL").unwrap_or(defaults.token_api_url),
+            price_api_url: std::env::var("JUPITER_PRICE_API_URL").unwrap_or(defaults.price_api_url),
+            api_key: std::env::var("JUPITER_API_KEY").ok().filter(|key| !key.is_empty()),
+            timeout,
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Config {
     pub network: SolanaNetwork,
     pub rpc_url: String,
     pub private_key: String,
     pub commitment: CommitmentConfig,
+    pub jupiter: JupiterConfig,
+    /// Upper bound on MCP requests handled at the same time
+    pub max_concurrent_requests: usize,
+    pub transport: Transport,
+    /// Listen address for the HTTP transport
+    pub http_bind: SocketAddr,
+    /// Browser origins allowed to call the HTTP transport besides localhost
+    pub http_allowed_origins: Vec<String>,
+    /// Bearer token every HTTP request must carry; required off loopback
+    pub http_token: Option<String>,
+    /// HTTP sessions unused for this long are dropped
+    pub http_session_ttl: Duration,
+    /// Upper bound on live HTTP sessions
+    pub http_max_sessions: usize,
+    /// How long a quote from `get_quote` stays executable
+    pub quote_ttl: Duration,
+    /// TOML or JSON file with the swap policy; no limits when unset
+    pub policy_path: Option<PathBuf>,
+    /// Where the fetched Jupiter token list is cached; disabled when unset
+    pub token_cache_path: Option<PathBuf>,
+    /// JSON Lines journal of every swap attempt; disabled when unset
+    pub journal_path: Option<PathBuf>,
+}
+
+/// Per-user directory for the server's files: `$<xdg_var>/jup-mcp`, or
+/// `$HOME/<home_subdir>/jup-mcp` when the XDG variable is unset
+fn user_dir(xdg_var: &str, home_subdir: &str) -> Option<PathBuf> {
+    let absolute = |var: &str| std::env::var_os(var).map(PathBuf::from).filter(|path| path.is_absolute());
+    let base = absolute(xdg_var).or_else(|| absolute("HOME").map(|home| home.join(home_subdir)))?;
+    Some(base.join("jup-mcp"))
 }
 
 impl Config {
@@ -65,11 +198,106 @@ impl Config {
                 "SOLANA_PRIVATE_KEY environment variable is required".to_string()
             ))?;
         
+        let max_concurrent_requests = match std::env::var("MCP_MAX_CONCURRENT_REQUESTS") {
+            Ok(limit) => limit.parse().ok().filter(|limit| *limit > 0).ok_or_else(|| {
+                JupiterMcpError::Environment(format!("Invalid MCP_MAX_CONCURRENT_REQUESTS: {}", limit))
+            })?,
+            Err(_) => 16,
+        };
+        
+        let transport: Transport = std::env::var("MCP_TRANSPORT")
+            .unwrap_or_else(|_| "stdio".to_string())
+            .parse()?;
+        
+        let http_bind = std::env::var("MCP_HTTP_BIND")
+            .unwrap_or_else(|_| "127.0.0.1:8080".to_string());
+        let http_bind = http_bind.parse().map_err(|_| JupiterMcpError::Environment(
+            format!("Invalid MCP_HTTP_BIND: {}", http_bind)
+        ))?;
+        
+        let http_allowed_origins = std::env::var("MCP_HTTP_ALLOWED_ORIGINS")
+            .map(|origins| {
+                origins
+                    .split(',')
+                    .map(|origin| origin.trim().to_string())
+                    .filter(|origin| !origin.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        
+        let http_session_ttl = match std::env::var("MCP_HTTP_SESSION_TTL_SECS") {
+            Ok(secs) => Duration::from_secs(secs.parse().map_err(|_| JupiterMcpError::Environment(
+                format!("Invalid MCP_HTTP_SESSION_TTL_SECS: {}", secs)
+            ))?),
+            Err(_) => Duration::from_secs(30 * 60),
+        };
+        
+        let http_max_sessions = match std::env::var("MCP_HTTP_MAX_SESSIONS") {
+            Ok(limit) => limit.parse().ok().filter(|limit| *limit > 0).ok_or_else(|| {
+                JupiterMcpError::Environment(format!("Invalid MCP_HTTP_MAX_SESSIONS: {}", limit))
+            })?,
+            Err(_) => 64,
+        };
+        
+        let quote_ttl = match std::env::var("QUOTE_TTL_SECS") {
+            Ok(secs) => Duration::from_secs(secs.parse().map_err(|_| JupiterMcpError::Environment(
+                format!("Invalid QUOTE_TTL_SECS: {}", secs)
+            ))?),
+            Err(_) => Duration::from_secs(60),
+        };
+        
+        // An empty TOKEN_CACHE_PATH turns the disk cache off. The default is
+        // per user, as a shared directory would let others plant mappings.
+        let token_cache_path = match std::env::var("TOKEN_CACHE_PATH") {
+            Ok(path) => Some(PathBuf::from(path)).filter(|path| !path.as_os_str().is_empty()),
+            Err(_) => user_dir("XDG_CACHE_HOME", ".cache").map(|dir| dir.join("tokens.json")),
+        };
+        
+        // The journal is an audit trail, so it defaults to a per-user data
+        // file that outlives the process, wherever the server is started from
+        let journal_path = match std::env::var("SWAP_JOURNAL_PATH") {
+            Ok(path) => Some(PathBuf::from(path)).filter(|path| !path.as_os_str().is_empty()),
+            Err(_) => user_dir("XDG_DATA_HOME", ".local/share").map(|dir| dir.join("swap-journal.jsonl")),
+        };
+        
         Ok(Config {
             network,
             rpc_url,
             private_key,
             commitment: CommitmentConfig::confirmed(),
+            jupiter: JupiterConfig::from_env()?,
+            max_concurrent_requests,
+            transport,
+            http_bind,
+            http_allowed_origins,
+            http_token: std::env::var("MCP_HTTP_TOKEN").ok().filter(|token| !token.is_empty()),
+            http_session_ttl,
+            http_max_sessions,
+            quote_ttl,
+            policy_path: std::env::var("SWAP_POLICY_PATH").ok().filter(|path| !path.is_empty()).map(PathBuf::from),
+            token_cache_path,
+            journal_path,
         })
     }
-}
\ No newline at end of file
+    
+    /// Apply command line overrides: `--transport <stdio|http>` and `--bind <addr>`
+    pub fn apply_args<I: IntoIterator<Item = String>>(&mut self, args: I) -> Result<()> {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = |flag: &str| args.next().ok_or_else(|| JupiterMcpError::InvalidInput(
+                format!("Missing value for {}", flag)
+            ));
+            match arg.as_str() {
+                "--transport" => self.transport = value("--transport")?.parse()?,
+                "--bind" => {
+                    let bind = value("--bind")?;
+                    self.http_bind = bind.parse().map_err(|_| JupiterMcpError::InvalidInput(
+                        format!("Invalid bind address: {}", bind)
+                    ))?;
+                }
+                _ => return Err(JupiterMcpError::InvalidInput(format!("Unknown argument: {}", arg))),
+            }
+        }
+        Ok(())
+    }
+}
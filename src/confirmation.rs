@@ -0,0 +1,127 @@
+use crate::session::Session;
+use crate::tools::get_quote::QuoteResponse;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tracing::warn;
+
+/// The user's answer to a swap confirmation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Confirmation {
+    Approved,
+    Declined(String),
+    /// The client did not declare the elicitation capability, or has no
+    /// stream open to receive the request
+    Unavailable,
+}
+
+/// Ask the user to approve a swap through `elicitation/create`, waiting up
+/// to `timeout` for the answer
+pub async fn confirm_swap(
+    session: &Session,
+    quote: &QuoteResponse,
+    reason: &str,
+    timeout: Duration,
+) -> Confirmation {
+    if !session.supports_elicitation() || !session.is_attached() {
+        return Confirmation::Unavailable;
+    }
+
+    let params = json!({
+        "message": confirmation_message(quote, reason),
+        "requestedSchema": {
+            "type": "object",
+            "properties": {
+                "approve": {
+                    "type": "boolean",
+                    "title": "Approve swap",
+                    "description": "Sign and send this swap"
+                }
+            },
+            "required": ["approve"]
+        }
+    });
+
+    match session.request("elicitation/create", params, timeout).await {
+        Ok(result) => interpret(&result),
+        Err(e) => {
+            warn!("Swap confirmation failed: {}", e);
+            Confirmation::Declined(e.to_string())
+        }
+    }
+}
+
+/// Only an accepted form with `approve: true` counts as approval
+fn interpret(result: &Value) -> Confirmation {
+    let action = result.get("action").and_then(Value::as_str).unwrap_or("unknown");
+    let approved = result.pointer("/content/approve").and_then(Value::as_bool).unwrap_or(false);
+
+    match (action, approved) {
+        ("accept", true) => Confirmation::Approved,
+        ("accept", false) => Confirmation::Declined("the user did not approve the swap".to_string()),
+        (action, _) => Confirmation::Declined(format!("the user chose to {} the confirmation", action)),
+    }
+}
+
+fn confirmation_message(quote: &QuoteResponse, reason: &str) -> String {
+    let route = quote
+        .route_plan
+        .iter()
+        .map(|step| format!("{} ({}%)", step.swap_info.label, step.percent))
+        .collect::<Vec<_>>()
+        .join(" → ");
+
+    format!(
+        "Approve this swap?\n\n\
+        Sell: {} of {}\n\
+        Receive: {} of {} (at least {})\n\
+        Route: {}\n\
+        Price impact: {}%\n\
+        Slippage: {} bps\n\n\
+        Confirmation required because {}.",
+        quote.in_amount,
+        quote.input_mint,
+        quote.out_amount,
+        quote.output_mint,
+        quote.other_amount_threshold,
+        if route.is_empty() { "unknown" } else { &route },
+        quote.price_impact_pct,
+        quote.slippage_bps,
+        reason
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::get_quote::test_quotes::sample_quote;
+
+    #[test]
+    fn test_interpret_requires_explicit_approval() {
+        assert_eq!(interpret(&json!({ "action": "accept", "content": { "approve": true } })), Confirmation::Approved);
+        assert!(matches!(interpret(&json!({ "action": "accept", "content": { "approve": false } })), Confirmation::Declined(_)));
+        assert!(matches!(interpret(&json!({ "action": "decline" })), Confirmation::Declined(_)));
+        assert!(matches!(interpret(&json!({ "action": "cancel" })), Confirmation::Declined(_)));
+    }
+
+    #[tokio::test]
+    async fn test_detached_session_is_unavailable() {
+        let (tx, _rx) = tokio::sync::mpsc::channel(1);
+        let session = Session::new(tx);
+        session.initialize(serde_json::from_value(json!({
+            "protocolVersion": "2025-06-18",
+            "capabilities": { "elicitation": {} }
+        })).unwrap());
+        session.set_attached(false);
+
+        let confirmation = confirm_swap(&session, &sample_quote(), "test", Duration::from_secs(1)).await;
+        assert_eq!(confirmation, Confirmation::Unavailable);
+    }
+
+    #[test]
+    fn test_message_lists_route_labels() {
+        let message = confirmation_message(&sample_quote(), "every swap requires approval");
+        assert!(message.contains("Route: Whirlpool (100%)"));
+        assert!(message.contains("Price impact: 0.12%"));
+        assert!(message.contains("at least 99000000"));
+    }
+}
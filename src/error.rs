@@ -3,7 +3,7 @@ use thiserror::Error;
 #[derive(Error, Debug)]
 pub enum JupiterMcpError {
     #[error("Solana client error: {0}")]
-    SolanaClient(#[from] solana_client::client_error::ClientError),
+    SolanaClient(#[from] Box<solana_client::client_error::ClientError>),
 
     #[error("Solana SDK error: {0}")]
     SolanaSdk(String),
@@ -31,6 +31,15 @@ pub enum JupiterMcpError {
 
     #[error("MCP protocol error: {0}")]
     McpProtocol(String),
+
+    #[error("Transaction signing error: {0}")]
+    Signing(String),
+}
+
+impl From<solana_client::client_error::ClientError> for JupiterMcpError {
+    fn from(e: solana_client::client_error::ClientError) -> Self {
+        JupiterMcpError::SolanaClient(Box::new(e))
+    }
 }
 
 pub type Result<T> = std::result::Result<T, JupiterMcpError>;
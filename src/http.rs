@@ -0,0 +1,542 @@
+use crate::config::Config;
+use crate::mcp::{McpRequest, McpResponse};
+use crate::server::McpServer;
+use crate::session::{Session, OUTBOUND_CAPACITY};
+use crate::{JupiterMcpError, Result};
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::post,
+    Json, Router,
+};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Header carrying the session id assigned on `initialize`
+pub const MCP_SESSION_ID: &str = "mcp-session-id";
+
+/// How often idle sessions are looked for
+const REAP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A client session held by the HTTP transport
+struct HttpSession {
+    session: Arc<Session>,
+    /// Server-initiated messages, drained by at most one GET stream at a time
+    outbound: Arc<tokio::sync::Mutex<mpsc::Receiver<Value>>>,
+    last_seen: Mutex<Instant>,
+}
+
+impl HttpSession {
+    fn touch(&self) {
+        *self.last_seen.lock().unwrap() = Instant::now();
+    }
+
+    /// Unused for `ttl`, with no request in flight and no open stream
+    fn is_idle(self: &Arc<Self>, ttl: Duration) -> bool {
+        Arc::strong_count(self) == 1
+            && self.last_seen.lock().unwrap().elapsed() >= ttl
+            && self.outbound.try_lock().is_ok()
+    }
+}
+
+struct HttpState {
+    server: Arc<McpServer>,
+    sessions: Mutex<HashMap<String, Arc<HttpSession>>>,
+}
+
+/// Build the Streamable HTTP router serving the MCP endpoint at `/mcp`
+pub fn router(server: Arc<McpServer>) -> Router {
+    let state = Arc::new(HttpState {
+        server,
+        sessions: Mutex::new(HashMap::new()),
+    });
+
+    tokio::spawn(reap_sessions(Arc::downgrade(&state)));
+    routes(state)
+}
+
+fn routes(state: Arc<HttpState>) -> Router {
+    Router::new()
+        .route("/mcp", post(handle_post).get(handle_get).delete(handle_delete))
+        .with_state(state)
+}
+
+/// Drop idle sessions until the router itself is dropped
+async fn reap_sessions(state: Weak<HttpState>) {
+    let mut interval = tokio::time::interval(REAP_INTERVAL);
+    loop {
+        interval.tick().await;
+        let Some(state) = state.upgrade() else {
+            return;
+        };
+        reap_idle(&state);
+    }
+}
+
+fn reap_idle(state: &HttpState) {
+    let ttl = state.server.config().http_session_ttl;
+    state.sessions.lock().unwrap().retain(|id, http_session| {
+        let idle = http_session.is_idle(ttl);
+        if idle {
+            info!("Expired idle HTTP session {}", id);
+        }
+        !idle
+    });
+}
+
+/// Run the MCP server using the Streamable HTTP transport
+pub async fn run_http(server: Arc<McpServer>) -> Result<()> {
+    check_bind(server.config())?;
+    let bind = server.config().http_bind;
+    info!("Jupiter AG MCP Server listening on http://{}/mcp", bind);
+
+    axum::Server::try_bind(&bind)
+        .map_err(|e| JupiterMcpError::McpProtocol(format!("Failed to bind {}: {}", bind, e)))?
+        .serve(router(server).into_make_service())
+        .await
+        .map_err(|e| JupiterMcpError::McpProtocol(format!("HTTP server error: {}", e)))?;
+
+    info!("Jupiter AG MCP Server shutting down");
+    Ok(())
+}
+
+/// Without a token anyone who can reach the port could sign swaps with the
+/// server wallet, so only loopback listeners may run without one
+fn check_bind(config: &Config) -> Result<()> {
+    if config.http_token.is_none() && !config.http_bind.ip().is_loopback() {
+        return Err(JupiterMcpError::Environment(format!(
+            "Refusing to listen on {} without MCP_HTTP_TOKEN; set a token or bind to a loopback address",
+            config.http_bind
+        )));
+    }
+    Ok(())
+}
+
+/// POST carries client requests and notifications, answered with JSON
+async fn handle_post(State(state): State<Arc<HttpState>>, headers: HeaderMap, body: String) -> Response {
+    if let Some(rejection) = check_origin(&state, &headers).or_else(|| check_token(&state, &headers)) {
+        return rejection;
+    }
+
+    let payload: Value = match serde_json::from_str(&body) {
+        Ok(payload) => payload,
+        Err(_) => return (StatusCode::BAD_REQUEST, Json(McpResponse::invalid_message(&body))).into_response(),
+    };
+
+    let (messages, is_batch) = match payload {
+        Value::Array(messages) => (messages, true),
+        message => (vec![message], false),
+    };
+
+    let is_initialize = messages
+        .iter()
+        .any(|message| message.get("method").and_then(Value::as_str) == Some("initialize"));
+
+    let (session_id, http_session, is_new) = match session_id(&headers) {
+        Some(id) => match state.sessions.lock().unwrap().get(&id) {
+            Some(http_session) => {
+                http_session.touch();
+                (id, Arc::clone(http_session), false)
+            }
+            None => return error_response(StatusCode::NOT_FOUND, "Session not found"),
+        },
+        None if is_initialize => {
+            if let Some(rejection) = check_capacity(&state) {
+                return rejection;
+            }
+            let (outbound_tx, outbound_rx) = mpsc::channel(OUTBOUND_CAPACITY);
+            let session = Arc::new(Session::new(outbound_tx));
+            // Nothing reads server-initiated messages until a GET stream opens
+            session.set_attached(false);
+            let http_session = Arc::new(HttpSession {
+                session,
+                outbound: Arc::new(tokio::sync::Mutex::new(outbound_rx)),
+                last_seen: Mutex::new(Instant::now()),
+            });
+            (Uuid::new_v4().to_string(), http_session, true)
+        }
+        None => return error_response(StatusCode::BAD_REQUEST, "Missing Mcp-Session-Id header"),
+    };
+
+    let handled = messages.into_iter().map(|message| {
+        let server = Arc::clone(&state.server);
+        let session = Arc::clone(&http_session.session);
+        async move {
+            // Responses to server-initiated requests are delivered to their waiter
+            if session.complete(&message) {
+                return None;
+            }
+            match serde_json::from_value::<McpRequest>(message.clone()) {
+                Ok(request) => server.handle_message(&session, request).await,
+                Err(_) => Some(McpResponse::invalid_message(&message.to_string())),
+            }
+        }
+    });
+    let responses: Vec<McpResponse> = futures::future::join_all(handled).await.into_iter().flatten().collect();
+
+    if is_new {
+        if !http_session.session.is_initialized() {
+            // Initialization failed, so there is no session to hand out
+            return respond(responses, is_batch, None);
+        }
+        if let Some(rejection) = check_capacity(&state) {
+            return rejection;
+        }
+        info!("Created HTTP session {}", session_id);
+        state.sessions.lock().unwrap().insert(session_id.clone(), http_session);
+    }
+
+    respond(responses, is_batch, Some(&session_id))
+}
+
+/// GET opens an SSE stream for server-initiated messages
+async fn handle_get(State(state): State<Arc<HttpState>>, headers: HeaderMap) -> Response {
+    if let Some(rejection) = check_origin(&state, &headers).or_else(|| check_token(&state, &headers)) {
+        return rejection;
+    }
+
+    let accepts_sse = headers
+        .get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .map(|accept| accept.contains("text/event-stream"))
+        .unwrap_or(false);
+    if !accepts_sse {
+        return error_response(StatusCode::NOT_ACCEPTABLE, "GET requires Accept: text/event-stream");
+    }
+
+    let http_session = match lookup_session(&state, &headers) {
+        Ok(http_session) => http_session,
+        Err((status, message)) => return error_response(status, message),
+    };
+
+    let receiver = match Arc::clone(&http_session.outbound).try_lock_owned() {
+        Ok(receiver) => receiver,
+        Err(_) => return error_response(StatusCode::CONFLICT, "An SSE stream is already open for this session"),
+    };
+
+    let attachment = Attachment::new(Arc::clone(&http_session.session));
+    let stream = futures::stream::unfold((receiver, attachment), |(mut receiver, attachment)| async move {
+        let message = receiver.recv().await?;
+        Some((Event::default().event("message").json_data(message), (receiver, attachment)))
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::default()).into_response()
+}
+
+/// Marks a session attached for as long as its GET stream is alive
+struct Attachment(Arc<Session>);
+
+impl Attachment {
+    fn new(session: Arc<Session>) -> Self {
+        session.set_attached(true);
+        Self(session)
+    }
+}
+
+impl Drop for Attachment {
+    fn drop(&mut self) {
+        self.0.set_attached(false);
+    }
+}
+
+/// DELETE ends a session
+async fn handle_delete(State(state): State<Arc<HttpState>>, headers: HeaderMap) -> Response {
+    if let Some(rejection) = check_origin(&state, &headers).or_else(|| check_token(&state, &headers)) {
+        return rejection;
+    }
+
+    let removed = session_id(&headers).and_then(|id| state.sessions.lock().unwrap().remove(&id));
+    match removed {
+        Some(_) => StatusCode::OK.into_response(),
+        None => error_response(StatusCode::NOT_FOUND, "Session not found"),
+    }
+}
+
+/// Refuse new sessions once `http_max_sessions` are live, after dropping idle ones
+fn check_capacity(state: &HttpState) -> Option<Response> {
+    let max_sessions = state.server.config().http_max_sessions;
+    if state.sessions.lock().unwrap().len() < max_sessions {
+        return None;
+    }
+    reap_idle(state);
+    if state.sessions.lock().unwrap().len() < max_sessions {
+        return None;
+    }
+
+    warn!("Rejected new HTTP session: {} sessions already open", max_sessions);
+    Some(error_response(StatusCode::SERVICE_UNAVAILABLE, "Too many open sessions"))
+}
+
+fn session_id(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(MCP_SESSION_ID)
+        .and_then(|id| id.to_str().ok())
+        .map(str::to_string)
+}
+
+fn lookup_session(state: &HttpState, headers: &HeaderMap) -> std::result::Result<Arc<HttpSession>, (StatusCode, &'static str)> {
+    let id = session_id(headers).ok_or((StatusCode::BAD_REQUEST, "Missing Mcp-Session-Id header"))?;
+    let http_session = state
+        .sessions
+        .lock()
+        .unwrap()
+        .get(&id)
+        .cloned()
+        .ok_or((StatusCode::NOT_FOUND, "Session not found"))?;
+    http_session.touch();
+    Ok(http_session)
+}
+
+/// Reject browser origins other than localhost and the configured allow list,
+/// which guards against DNS rebinding
+fn check_origin(state: &HttpState, headers: &HeaderMap) -> Option<Response> {
+    let origin = headers.get(header::ORIGIN)?.to_str().unwrap_or_default();
+    let allowed = &state.server.config().http_allowed_origins;
+
+    if is_local_origin(origin) || allowed.iter().any(|allowed| allowed == origin) {
+        return None;
+    }
+
+    warn!("Rejected request from origin {}", origin);
+    Some(error_response(StatusCode::FORBIDDEN, "Origin not allowed"))
+}
+
+/// Require `Authorization: Bearer <MCP_HTTP_TOKEN>` when a token is configured
+fn check_token(state: &HttpState, headers: &HeaderMap) -> Option<Response> {
+    let expected = state.server.config().http_token.as_ref()?;
+    let presented = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .unwrap_or_default();
+
+    if constant_time_eq(presented.trim().as_bytes(), expected.as_bytes()) {
+        return None;
+    }
+
+    warn!("Rejected HTTP request with a missing or wrong bearer token");
+    let mut response = error_response(StatusCode::UNAUTHORIZED, "Missing or invalid bearer token");
+    response.headers_mut().insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+    Some(response)
+}
+
+/// Compare secrets without leaking the position of the first mismatch
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+fn is_local_origin(origin: &str) -> bool {
+    let host = origin.split("://").nth(1).unwrap_or(origin);
+    let host = match host.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next().unwrap_or_default(),
+        None => host.split([':', '/']).next().unwrap_or_default(),
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "::1")
+}
+
+fn respond(responses: Vec<McpResponse>, is_batch: bool, session_id: Option<&str>) -> Response {
+    let mut response = match (responses.len(), is_batch) {
+        (0, _) => StatusCode::ACCEPTED.into_response(),
+        (_, true) => Json(responses).into_response(),
+        (_, false) => Json(responses.into_iter().next()).into_response(),
+    };
+
+    if let Some(value) = session_id.and_then(|id| HeaderValue::from_str(id).ok()) {
+        response.headers_mut().insert(MCP_SESSION_ID, value);
+    }
+    response
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response {
+    (status, message.to_string()).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::tests::test_config;
+    use axum::body::Body;
+    use axum::http::Request;
+    use serde_json::json;
+    use tower::ServiceExt;
+
+    fn test_router() -> Router {
+        router(Arc::new(McpServer::new(test_config()).unwrap()))
+    }
+
+    fn ping() -> Value {
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "ping" })
+    }
+
+    fn post_request(session: Option<&str>, body: Value) -> Request<Body> {
+        let mut builder = Request::post("/mcp")
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::ACCEPT, "application/json, text/event-stream");
+        if let Some(session) = session {
+            builder = builder.header(MCP_SESSION_ID, session);
+        }
+        builder.body(Body::from(body.to_string())).unwrap()
+    }
+
+    async fn body_json(response: Response) -> Value {
+        let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    async fn initialize(app: &Router) -> String {
+        let response = app
+            .clone()
+            .oneshot(post_request(None, json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": { "protocolVersion": "2025-06-18", "capabilities": {} }
+            })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        response.headers()[MCP_SESSION_ID].to_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_session_lifecycle() {
+        let app = test_router();
+        let session = initialize(&app).await;
+
+        let response = app
+            .clone()
+            .oneshot(post_request(Some(&session), json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body_json(response).await["id"], json!(2));
+
+        let response = app
+            .clone()
+            .oneshot(post_request(Some(&session), json!({ "jsonrpc": "2.0", "method": "notifications/initialized" })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+        let delete = Request::delete("/mcp").header(MCP_SESSION_ID, &session).body(Body::empty()).unwrap();
+        assert_eq!(app.clone().oneshot(delete).await.unwrap().status(), StatusCode::OK);
+
+        let response = app
+            .clone()
+            .oneshot(post_request(Some(&session), json!({ "jsonrpc": "2.0", "id": 3, "method": "ping" })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_idle_sessions_expire() {
+        let mut config = test_config();
+        config.http_session_ttl = Duration::ZERO;
+        let state = Arc::new(HttpState {
+            server: Arc::new(McpServer::new(config).unwrap()),
+            sessions: Mutex::new(HashMap::new()),
+        });
+        let app = routes(Arc::clone(&state));
+
+        let session = initialize(&app).await;
+        assert_eq!(state.sessions.lock().unwrap().len(), 1);
+
+        reap_idle(&state);
+        assert!(state.sessions.lock().unwrap().is_empty());
+
+        let response = app
+            .oneshot(post_request(Some(&session), json!({ "jsonrpc": "2.0", "id": 2, "method": "ping" })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_session_limit() {
+        let mut config = test_config();
+        config.http_max_sessions = 1;
+        let app = router(Arc::new(McpServer::new(config).unwrap()));
+        initialize(&app).await;
+
+        let response = app
+            .oneshot(post_request(None, json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": { "protocolVersion": "2025-06-18", "capabilities": {} }
+            })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_request_without_session_is_rejected() {
+        let app = test_router();
+        let response = app
+            .oneshot(post_request(None, json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_foreign_origin_is_rejected() {
+        let app = test_router();
+        let mut request = post_request(None, ping());
+        request.headers_mut().insert(header::ORIGIN, HeaderValue::from_static("https://evil.example"));
+
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_bearer_token_is_required_when_configured() {
+        let mut config = test_config();
+        config.http_token = Some("secret".to_string());
+        let app = router(Arc::new(McpServer::new(config).unwrap()));
+
+        let response = app.clone().oneshot(post_request(None, ping())).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(response.headers()[header::WWW_AUTHENTICATE], "Bearer");
+
+        let mut request = post_request(None, ping());
+        request.headers_mut().insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer wrong"));
+        assert_eq!(app.clone().oneshot(request).await.unwrap().status(), StatusCode::UNAUTHORIZED);
+
+        // Past authentication, a ping without a session is a client error
+        let mut request = post_request(None, ping());
+        request.headers_mut().insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer secret"));
+        assert_eq!(app.oneshot(request).await.unwrap().status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_public_bind_requires_token() {
+        let mut config = test_config();
+        assert!(check_bind(&config).is_ok());
+
+        config.http_bind = "0.0.0.0:8080".parse().unwrap();
+        assert!(check_bind(&config).is_err());
+
+        config.http_token = Some("secret".to_string());
+        assert!(check_bind(&config).is_ok());
+    }
+
+    #[test]
+    fn test_is_local_origin() {
+        assert!(is_local_origin("http://localhost:3000"));
+        assert!(is_local_origin("http://127.0.0.1"));
+        assert!(is_local_origin("http://[::1]:8080"));
+        assert!(!is_local_origin("https://localhost.evil.example"));
+    }
+}
@@ -0,0 +1,328 @@
+use crate::policy::{PolicyViolation, SECONDS_PER_DAY};
+use crate::settlement::RealizedSwap;
+use crate::tools::get_quote::QuoteResponse;
+use crate::utils::create_private_dir;
+use crate::{JupiterMcpError, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+/// How a swap attempt ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SwapStatus {
+    /// Landed and succeeded
+    Confirmed,
+    /// Landed, but a program returned an error
+    Failed,
+    /// The blockhash expired before it landed
+    Expired,
+    /// Blocked by the policy or by the user before anything was signed
+    Rejected,
+    /// Could not be submitted, e.g. Jupiter or the RPC node returned an error.
+    /// With a signature, it was sent but its outcome is unknown.
+    Error,
+}
+
+impl std::str::FromStr for SwapStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        serde_json::from_value(serde_json::Value::String(s.to_lowercase()))
+            .map_err(|_| format!("Unknown swap status '{}'", s))
+    }
+}
+
+/// What the policy and the user decided about a swap
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PolicyDecision {
+    /// Rule that blocked the swap, if any
+    pub rule: Option<String>,
+    pub message: Option<String>,
+    /// Why the user was asked to confirm
+    #[serde(rename = "confirmationReason")]
+    pub confirmation_reason: Option<String>,
+    /// "approved", "declined" or "unavailable"
+    pub confirmation: Option<String>,
+}
+
+impl PolicyDecision {
+    pub fn violated(&mut self, violation: &PolicyViolation) {
+        self.rule = Some(violation.rule.to_string());
+        self.message = Some(violation.message.clone());
+    }
+}
+
+/// One swap attempt made by `execute_swap`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Unix seconds
+    pub timestamp: u64,
+    #[serde(rename = "quoteId")]
+    pub quote_id: String,
+    pub quote: QuoteResponse,
+    pub policy: PolicyDecision,
+    pub status: SwapStatus,
+    pub signature: Option<String>,
+    pub error: Option<String>,
+    pub realized: Option<RealizedSwap>,
+}
+
+impl JournalEntry {
+    pub fn new(quote_id: &str, quote: &QuoteResponse, policy: PolicyDecision, status: SwapStatus) -> Self {
+        // The attached Ultra transaction is large and already signed away
+        let mut quote = quote.clone();
+        quote.extra.remove("transaction");
+
+        Self {
+            timestamp: now(),
+            quote_id: quote_id.to_string(),
+            quote,
+            policy,
+            status,
+            signature: None,
+            error: None,
+            realized: None,
+        }
+    }
+
+    /// Whether the swap may have spent its input: it landed, or was sent
+    /// with an outcome that was never learned
+    pub fn may_have_spent(&self) -> bool {
+        match self.status {
+            SwapStatus::Confirmed | SwapStatus::Failed => true,
+            SwapStatus::Error => self.signature.is_some(),
+            SwapStatus::Expired | SwapStatus::Rejected => false,
+        }
+    }
+}
+
+/// Which entries `SwapJournal::query` returns
+#[derive(Debug, Clone, Default)]
+pub struct JournalFilter {
+    /// Unix seconds, inclusive
+    pub since: Option<u64>,
+    /// Unix seconds, inclusive
+    pub until: Option<u64>,
+    /// Matches either side of the swap
+    pub mint: Option<String>,
+    pub status: Option<SwapStatus>,
+}
+
+impl JournalFilter {
+    fn matches(&self, entry: &JournalEntry) -> bool {
+        self.since.is_none_or(|since| entry.timestamp >= since)
+            && self.until.is_none_or(|until| entry.timestamp <= until)
+            && self.mint.as_ref().is_none_or(|mint| {
+                entry.quote.input_mint == *mint || entry.quote.output_mint == *mint
+            })
+            && self.status.is_none_or(|status| entry.status == status)
+    }
+}
+
+/// Append-only JSON Lines log of swap attempts
+#[derive(Debug)]
+pub struct SwapJournal {
+    path: Option<PathBuf>,
+    /// Keeps concurrent appends from interleaving
+    lock: Mutex<()>,
+}
+
+impl SwapJournal {
+    /// A journal at `path`, or one that records nothing when unset
+    pub fn new(path: Option<PathBuf>) -> Self {
+        Self { path, lock: Mutex::new(()) }
+    }
+
+    /// A journal at `path` that is known to be writable, so a bad path fails
+    /// at startup instead of losing the record of later swaps
+    pub fn open(path: Option<PathBuf>) -> Result<Self> {
+        if let Some(path) = &path {
+            open_for_append(path).map_err(|e| JupiterMcpError::Environment(format!(
+                "Cannot write the swap journal {}: {}; set SWAP_JOURNAL_PATH to a writable file, or empty to disable it",
+                path.display(),
+                e
+            )))?;
+        }
+        Ok(Self::new(path))
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Append an entry. Failures are logged rather than returned, since the
+    /// swap they describe has already happened.
+    pub fn record(&self, entry: &JournalEntry) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = append(path, entry) {
+            warn!("Failed to write swap journal {}: {}", path.display(), e);
+        }
+    }
+
+    /// Entries matching the filter, oldest first
+    pub fn query(&self, filter: &JournalFilter) -> Result<Vec<JournalEntry>> {
+        let Some(path) = &self.path else {
+            return Ok(Vec::new());
+        };
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+
+        let file = match std::fs::File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut entries = Vec::new();
+        for (number, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<JournalEntry>(&line) {
+                Ok(entry) if filter.matches(&entry) => entries.push(entry),
+                Ok(_) => {}
+                Err(e) => warn!("Skipping unreadable line {} of {}: {}", number + 1, path.display(), e),
+            }
+        }
+        Ok(entries)
+    }
+}
+
+fn append(path: &Path, entry: &JournalEntry) -> Result<()> {
+    let mut line = serde_json::to_vec(entry)?;
+    line.push(b'\n');
+    open_for_append(path)?.write_all(&line)?;
+    Ok(())
+}
+
+/// Open the journal for appending, creating it readable only by this user
+fn open_for_append(path: &Path) -> std::io::Result<File> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        create_private_dir(dir)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or(0)
+}
+
+/// Unix seconds at 00:00 UTC today
+pub fn start_of_today() -> u64 {
+    let now = now();
+    now - now % SECONDS_PER_DAY
+}
+
+/// Format unix seconds as an RFC 3339 UTC timestamp
+pub fn format_timestamp(timestamp: u64) -> String {
+    // Civil date from days since the epoch, after Howard Hinnant's algorithm
+    let days = (timestamp / SECONDS_PER_DAY) as i64;
+    let seconds = timestamp % SECONDS_PER_DAY;
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::get_quote::test_quotes::{sample_quote, JUP, SOL, USDC};
+    use serde_json::json;
+
+    fn entry(input: &str, output: &str, status: SwapStatus, timestamp: u64) -> JournalEntry {
+        let mut quote = QuoteResponse {
+            input_mint: input.to_string(),
+            output_mint: output.to_string(),
+            ..sample_quote()
+        };
+        quote.extra.insert("transaction".to_string(), json!("AQID"));
+
+        let mut entry = JournalEntry::new("quote", &quote, PolicyDecision::default(), status);
+        entry.timestamp = timestamp;
+        entry
+    }
+
+    #[test]
+    fn test_journal_round_trip_and_filters() {
+        let path = std::env::temp_dir().join(format!("jup-mcp-journal-{}.jsonl", uuid::Uuid::new_v4()));
+        let journal = SwapJournal::new(Some(path.clone()));
+
+        journal.record(&entry(SOL, USDC, SwapStatus::Confirmed, 100));
+        journal.record(&entry(USDC, JUP, SwapStatus::Rejected, 200));
+        journal.record(&entry(SOL, JUP, SwapStatus::Expired, 300));
+
+        let all = journal.query(&JournalFilter::default()).unwrap();
+        assert_eq!(all.len(), 3);
+        assert!(all[0].quote.transaction().is_none());
+
+        let by_mint = journal.query(&JournalFilter { mint: Some(USDC.to_string()), ..Default::default() }).unwrap();
+        assert_eq!(by_mint.iter().map(|entry| entry.timestamp).collect::<Vec<_>>(), vec![100, 200]);
+
+        let by_time = journal.query(&JournalFilter { since: Some(150), until: Some(300), ..Default::default() }).unwrap();
+        assert_eq!(by_time.len(), 2);
+
+        let by_status = journal.query(&JournalFilter { status: Some(SwapStatus::Expired), ..Default::default() }).unwrap();
+        assert_eq!(by_status.len(), 1);
+        assert_eq!(by_status[0].quote.output_mint, JUP);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_open_checks_the_path() {
+        let dir = std::env::temp_dir().join(format!("jup-mcp-journal-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("swap-journal.jsonl");
+        let journal = SwapJournal::open(Some(path.clone())).unwrap();
+        assert!(path.exists());
+        assert!(journal.query(&JournalFilter::default()).unwrap().is_empty());
+
+        // A regular file where the directory should be
+        let blocked = path.join("swap-journal.jsonl");
+        let error = SwapJournal::open(Some(blocked)).unwrap_err();
+        assert!(error.to_string().contains("SWAP_JOURNAL_PATH"));
+
+        assert!(SwapJournal::open(None).unwrap().path().is_none());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_timestamp(1_628_633_791), "2021-08-10T22:16:31Z");
+    }
+
+    #[test]
+    fn test_parse_status() {
+        assert_eq!("Confirmed".parse::<SwapStatus>(), Ok(SwapStatus::Confirmed));
+        assert!("landed".parse::<SwapStatus>().is_err());
+    }
+}
@@ -0,0 +1,231 @@
+use crate::config::{JupiterBackend, JupiterConfig};
+use crate::tokens::TokenInfo;
+use crate::tools::execute_swap::{ExecuteResponse, SwapResponse};
+use crate::tools::get_quote::QuoteResponse;
+use crate::{JupiterMcpError, Result};
+use reqwest::header::{HeaderMap, HeaderValue};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use serde_json::json;
+
+/// Header Jupiter uses to authenticate paid API plans
+const API_KEY_HEADER: &str = "x-api-key";
+
+/// Most mints the Price API accepts per request
+const MAX_PRICE_IDS: usize = 50;
+
+#[derive(Debug, Deserialize)]
+struct TokenPrice {
+    #[serde(rename = "usdPrice")]
+    usd_price: f64,
+}
+
+/// Client for the Jupiter HTTP APIs, sharing one connection pool across tools
+#[derive(Debug, Clone)]
+pub struct JupiterClient {
+    http: reqwest::Client,
+    config: JupiterConfig,
+}
+
+impl JupiterClient {
+    pub fn new(config: &JupiterConfig) -> Result<Self> {
+        let mut headers = HeaderMap::new();
+        if let Some(api_key) = &config.api_key {
+            let mut value = HeaderValue::from_str(api_key).map_err(|_| {
+                JupiterMcpError::Environment("JUPITER_API_KEY is not a valid header value".to_string())
+            })?;
+            value.set_sensitive(true);
+            headers.insert(API_KEY_HEADER, value);
+        }
+
+        let http = reqwest::Client::builder()
+            .user_agent(concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION")))
+            .timeout(config.timeout)
+            .default_headers(headers)
+            .build()?;
+
+        Ok(Self {
+            http,
+            config: config.clone(),
+        })
+    }
+
+    pub fn backend(&self) -> JupiterBackend {
+        self.config.backend
+    }
+
+    /// Ultra `GET /order`: a quote plus an unsigned transaction for the taker
+    pub async fn order<Q: Serialize + ?Sized>(&self, params: &Q) -> Result<QuoteResponse> {
+        self.get(&self.config.ultra_api_url, "/order", params).await
+    }
+
+    /// Ultra `POST /execute`: land a transaction signed from an `/order`
+    pub async fn execute(&self, signed_transaction: &str, request_id: &str) -> Result<ExecuteResponse> {
+        let body = json!({
+            "signedTransaction": signed_transaction,
+            "requestId": request_id,
+        });
+        self.post(&self.config.ultra_api_url, "/execute", &body).await
+    }
+
+    /// v6 `GET /quote`
+    pub async fn quote<Q: Serialize + ?Sized>(&self, params: &Q) -> Result<QuoteResponse> {
+        self.get(&self.config.swap_api_url, "/quote", params).await
+    }
+
+    /// v6 `POST /swap`: build a transaction for a quote
+    pub async fn swap<B: Serialize + ?Sized>(&self, body: &B) -> Result<SwapResponse> {
+        self.post(&self.config.swap_api_url, "/swap", body).await
+    }
+
+    /// Token API: the verified token list used to resolve symbols
+    pub async fn tokens(&self) -> Result<Vec<TokenInfo>> {
+        self.get(&self.config.token_api_url, "/tagged/verified", &()).await
+    }
+
+    /// Price API: USD prices for up to `MAX_PRICE_IDS` mints; unpriced mints are omitted
+    pub async fn prices(&self, mints: &[String]) -> Result<HashMap<String, f64>> {
+        let mut prices = HashMap::new();
+        for chunk in mints.chunks(MAX_PRICE_IDS) {
+            let response: HashMap<String, Option<TokenPrice>> = self
+                .get(&self.config.price_api_url, "", &[("ids", chunk.join(","))])
+                .await?;
+            prices.extend(response.into_iter().filter_map(|(mint, price)| Some((mint, price?.usd_price))));
+        }
+        Ok(prices)
+    }
+
+    async fn get<T, Q>(&self, base_url: &str, path: &str, params: &Q) -> Result<T>
+    where
+        T: DeserializeOwned,
+        Q: Serialize + ?Sized,
+    {
+        let response = self.http.get(join_url(base_url, path)).query(params).send().await?;
+        Self::parse(path, response).await
+    }
+
+    async fn post<T, B>(&self, base_url: &str, path: &str, body: &B) -> Result<T>
+    where
+        T: DeserializeOwned,
+        B: Serialize + ?Sized,
+    {
+        let response = self.http.post(join_url(base_url, path)).json(body).send().await?;
+        Self::parse(path, response).await
+    }
+
+    async fn parse<T: DeserializeOwned>(path: &str, response: reqwest::Response) -> Result<T> {
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(JupiterMcpError::JupiterApi(format!(
+                "Jupiter {} error {}: {}",
+                path, status, error_text
+            )));
+        }
+
+        Ok(response.json().await?)
+    }
+}
+
+fn join_url(base_url: &str, path: &str) -> String {
+    format!("{}{}", base_url.trim_end_matches('/'), path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serve a single canned JSON response and hand back the raw request
+    async fn mock_jupiter(status: &'static str, body: &'static str) -> (String, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handle = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 8192];
+            let n = socket.read(&mut buf).await.unwrap();
+            let response = format!(
+                "HTTP/1.1 {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&buf[..n]).to_string()
+        });
+        (format!("http://{}/", addr), handle)
+    }
+
+    #[test]
+    fn test_join_url() {
+        assert_eq!(join_url("https://quote-api.jup.ag/v6/", "/swap"), "https://quote-api.jup.ag/v6/swap");
+        assert_eq!(join_url("https://ultra-api.jup.ag", "/order"), "https://ultra-api.jup.ag/order");
+    }
+
+    #[tokio::test]
+    async fn test_execute_against_mock_server() {
+        let (url, request) = mock_jupiter("200 OK", r#"{"status":"Success","signature":"abc","slot":"42"}"#).await;
+        let client = JupiterClient::new(&JupiterConfig {
+            ultra_api_url: url,
+            api_key: Some("secret".to_string()),
+            ..JupiterConfig::default()
+        })
+        .unwrap();
+
+        let response = client.execute("dHg=", "req-1").await.unwrap();
+        assert_eq!(response.status, "Success");
+        assert_eq!(response.slot.as_deref(), Some("42"));
+
+        let request = request.await.unwrap();
+        assert!(request.starts_with("POST /execute "));
+        assert!(request.contains("x-api-key: secret"));
+        assert!(request.contains(r#""requestId":"req-1""#));
+    }
+
+    #[tokio::test]
+    async fn test_tokens_accepts_v2_ids() {
+        let (url, request) = mock_jupiter("200 OK", r#"[{"id":"So11111111111111111111111111111111111111112","symbol":"SOL","name":"Wrapped SOL","decimals":9,"tags":["verified"]}]"#).await;
+        let client = JupiterClient::new(&JupiterConfig {
+            token_api_url: url,
+            ..JupiterConfig::default()
+        })
+        .unwrap();
+
+        let tokens = client.tokens().await.unwrap();
+        assert_eq!(tokens[0].address, "So11111111111111111111111111111111111111112");
+        assert!(request.await.unwrap().starts_with("GET /tagged/verified "));
+    }
+
+    #[tokio::test]
+    async fn test_prices_skip_unpriced_mints() {
+        let (url, request) = mock_jupiter("200 OK", r#"{"So11111111111111111111111111111111111111112":{"usdPrice":147.5,"decimals":9},"unknown":null}"#).await;
+        let client = JupiterClient::new(&JupiterConfig {
+            price_api_url: url,
+            ..JupiterConfig::default()
+        })
+        .unwrap();
+
+        let prices = client.prices(&["So11111111111111111111111111111111111111112".to_string(), "unknown".to_string()]).await.unwrap();
+        assert_eq!(prices.len(), 1);
+        assert_eq!(prices["So11111111111111111111111111111111111111112"], 147.5);
+        assert!(request.await.unwrap().contains("ids=So11111111111111111111111111111111111111112%2Cunknown"));
+    }
+
+    #[tokio::test]
+    async fn test_error_status_is_reported() {
+        let (url, _request) = mock_jupiter("400 Bad Request", r#"{"error":"Could not find any route"}"#).await;
+        let client = JupiterClient::new(&JupiterConfig {
+            swap_api_url: url,
+            ..JupiterConfig::default()
+        })
+        .unwrap();
+
+        let err = client.quote(&[("amount", "1")]).await.unwrap_err();
+        assert!(err.to_string().contains("Could not find any route"));
+    }
+}
@@ -0,0 +1,293 @@
+use crate::{JupiterMcpError, Result};
+use serde::Serialize;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcSendTransactionConfig, RpcTransactionConfig};
+use solana_sdk::{
+    clock::MAX_PROCESSING_AGE,
+    commitment_config::CommitmentConfig,
+    signature::Signature,
+    transaction::VersionedTransaction,
+};
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, TransactionConfirmationStatus, TransactionStatus,
+    UiTransactionEncoding,
+};
+use std::time::Duration;
+use tokio::time::{sleep, Instant};
+use tracing::{info, warn};
+
+/// How often `getSignatureStatuses` is polled
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How long to wait before sending the same transaction again
+const REBROADCAST_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Attempts at fetching a landed transaction before giving up on its fee
+const FETCH_ATTEMPTS: usize = 3;
+
+/// How long the RPC node may keep failing before the outcome is given up as
+/// unknown; longer than a blockhash stays valid
+const MAX_RPC_OUTAGE: Duration = Duration::from_secs(120);
+
+/// How a broadcast transaction ended
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum LandingOutcome {
+    /// Landed and succeeded at the client's commitment
+    Confirmed {
+        slot: u64,
+        fee: Option<u64>,
+        commitment: TransactionConfirmationStatus,
+    },
+    /// Landed, but a program returned an error; the fee is still charged
+    Failed {
+        slot: u64,
+        fee: Option<u64>,
+        commitment: TransactionConfirmationStatus,
+        error: String,
+    },
+    /// The blockhash expired before the transaction landed, so it never will
+    Expired {
+        #[serde(rename = "lastValidBlockHeight")]
+        last_valid_block_height: u64,
+        #[serde(rename = "blockHeight")]
+        block_height: u64,
+    },
+}
+
+/// Result of sending a transaction until it lands or expires
+#[derive(Debug, Serialize)]
+pub struct Landing {
+    pub signature: String,
+    #[serde(flatten)]
+    pub outcome: LandingOutcome,
+    /// The landed transaction with its metadata, when it could be fetched
+    #[serde(skip)]
+    pub transaction: Option<EncodedConfirmedTransactionWithStatusMeta>,
+}
+
+/// Broadcast a signed transaction and track it to a final outcome.
+///
+/// The transaction is resent with `skip_preflight` every couple of seconds
+/// while `getSignatureStatuses` is polled, until it reaches the client's
+/// commitment or the block height passes `last_valid_block_height`. When the
+/// caller does not know that height it is estimated from the current one,
+/// which can only overstate how long the blockhash stays valid. RPC errors
+/// while polling are logged and retried; only an outage longer than
+/// `MAX_RPC_OUTAGE` is returned, as the transaction may still land.
+pub async fn send_and_confirm(
+    rpc: &RpcClient,
+    transaction: &VersionedTransaction,
+    last_valid_block_height: Option<u64>,
+) -> Result<Landing> {
+    let signature = *transaction.signatures.first().ok_or_else(|| {
+        JupiterMcpError::Signing("Transaction has no signature to track".to_string())
+    })?;
+    let commitment = rpc.commitment();
+    let last_valid_block_height = match last_valid_block_height {
+        Some(height) => height,
+        None => rpc.get_block_height().await? + MAX_PROCESSING_AGE as u64,
+    };
+
+    let send_config = RpcSendTransactionConfig {
+        skip_preflight: true,
+        max_retries: Some(0),
+        ..Default::default()
+    };
+
+    rpc.send_transaction_with_config(transaction, send_config).await?;
+    let mut last_sent = Instant::now();
+    info!("Sent transaction {}, valid until block height {}", signature, last_valid_block_height);
+
+    let mut failing_since = None;
+    loop {
+        sleep(POLL_INTERVAL).await;
+
+        match progress(rpc, &signature, commitment, last_valid_block_height).await {
+            Ok(Progress::Landed(status)) => return Ok(landed(rpc, signature, status).await),
+            Ok(Progress::Expired { block_height }) => {
+                return Ok(Landing {
+                    signature: signature.to_string(),
+                    outcome: LandingOutcome::Expired { last_valid_block_height, block_height },
+                    transaction: None,
+                });
+            }
+            // Seen at a lower commitment: wait for it rather than resending
+            Ok(Progress::Seen) => {
+                failing_since = None;
+                continue;
+            }
+            Ok(Progress::Unseen) => failing_since = None,
+            // The transaction is out there, so a flaky node must not end the
+            // wait before its blockhash could have expired
+            Err(e) => {
+                let since = *failing_since.get_or_insert_with(Instant::now);
+                if since.elapsed() >= MAX_RPC_OUTAGE {
+                    return Err(e);
+                }
+                warn!("Could not check on {}, retrying: {}", signature, e);
+            }
+        }
+
+        if last_sent.elapsed() >= REBROADCAST_INTERVAL {
+            if let Err(e) = rpc.send_transaction_with_config(transaction, send_config).await {
+                warn!("Failed to rebroadcast {}: {}", signature, e);
+            }
+            last_sent = Instant::now();
+        }
+    }
+}
+
+/// Where a sent transaction stands after one poll
+enum Progress {
+    Landed(TransactionStatus),
+    /// Seen below the target commitment
+    Seen,
+    /// Not seen, but its blockhash is still valid
+    Unseen,
+    /// Not seen, and its blockhash has expired
+    Expired { block_height: u64 },
+}
+
+async fn progress(
+    rpc: &RpcClient,
+    signature: &Signature,
+    commitment: CommitmentConfig,
+    last_valid_block_height: u64,
+) -> Result<Progress> {
+    let seen = |status: TransactionStatus| {
+        if status.satisfies_commitment(commitment) {
+            Progress::Landed(status)
+        } else {
+            Progress::Seen
+        }
+    };
+
+    if let Some(status) = signature_status(rpc, signature).await? {
+        return Ok(seen(status));
+    }
+    let block_height = rpc.get_block_height().await?;
+    if block_height <= last_valid_block_height {
+        return Ok(Progress::Unseen);
+    }
+    // It may have landed in the last valid block since the previous poll
+    Ok(match signature_status(rpc, signature).await? {
+        Some(status) => seen(status),
+        None => Progress::Expired { block_height },
+    })
+}
+
+/// Fetch a landed transaction with its status metadata, retrying while the
+/// RPC node catches up
+pub async fn fetch_transaction(
+    rpc: &RpcClient,
+    signature: &Signature,
+) -> Result<EncodedConfirmedTransactionWithStatusMeta> {
+    // getTransaction does not serve processed transactions
+    let commitment = match rpc.commitment() {
+        commitment if commitment.is_at_least_confirmed() => commitment,
+        _ => CommitmentConfig::confirmed(),
+    };
+    let config = RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Json),
+        commitment: Some(commitment),
+        max_supported_transaction_version: Some(0),
+    };
+
+    let mut attempt = 1;
+    loop {
+        match rpc.get_transaction_with_config(signature, config).await {
+            Ok(transaction) => return Ok(transaction),
+            Err(e) if attempt >= FETCH_ATTEMPTS => return Err(e.into()),
+            Err(_) => {
+                attempt += 1;
+                sleep(POLL_INTERVAL).await;
+            }
+        }
+    }
+}
+
+async fn signature_status(rpc: &RpcClient, signature: &Signature) -> Result<Option<TransactionStatus>> {
+    let statuses = rpc.get_signature_statuses(&[*signature]).await?;
+    Ok(statuses.value.into_iter().next().flatten())
+}
+
+/// Build the outcome of a transaction that reached the target commitment
+async fn landed(rpc: &RpcClient, signature: Signature, status: TransactionStatus) -> Landing {
+    let transaction = match fetch_transaction(rpc, &signature).await {
+        Ok(transaction) => Some(transaction),
+        Err(e) => {
+            warn!("Could not fetch landed transaction {}: {}", signature, e);
+            None
+        }
+    };
+    let fee = transaction.as_ref()
+        .and_then(|transaction| transaction.transaction.meta.as_ref())
+        .map(|meta| meta.fee);
+    let commitment = status.confirmation_status();
+
+    let outcome = match status.err {
+        None => LandingOutcome::Confirmed { slot: status.slot, fee, commitment },
+        Some(error) => LandingOutcome::Failed {
+            slot: status.slot,
+            fee,
+            commitment,
+            error: error.to_string(),
+        },
+    };
+
+    Landing { signature: signature.to_string(), outcome, transaction }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use solana_sdk::{
+        hash::Hash,
+        message::{Message, VersionedMessage},
+        pubkey::Pubkey,
+        signature::{Keypair, Signer},
+        system_instruction,
+    };
+
+    fn signed_transaction() -> VersionedTransaction {
+        let wallet = Keypair::new();
+        let instruction = system_instruction::transfer(&wallet.pubkey(), &Pubkey::new_unique(), 1);
+        let message = Message::new_with_blockhash(&[instruction], Some(&wallet.pubkey()), &Hash::new_unique());
+        VersionedTransaction::try_new(VersionedMessage::Legacy(message), &[&wallet]).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_confirmed_transaction() {
+        let rpc = RpcClient::new_mock("succeeds".to_string());
+        let landing = send_and_confirm(&rpc, &signed_transaction(), Some(2000)).await.unwrap();
+
+        assert_eq!(landing.outcome, LandingOutcome::Confirmed {
+            slot: 1,
+            fee: Some(0),
+            commitment: TransactionConfirmationStatus::Finalized,
+        });
+    }
+
+    #[tokio::test]
+    async fn test_failed_transaction() {
+        let rpc = RpcClient::new_mock("instruction_error".to_string());
+        let landing = send_and_confirm(&rpc, &signed_transaction(), Some(2000)).await.unwrap();
+
+        assert!(matches!(landing.outcome, LandingOutcome::Failed { slot: 1, ref error, .. } if error.contains("Instruction 0")));
+    }
+
+    #[tokio::test]
+    async fn test_expired_transaction() {
+        // The mock node reports block height 1234
+        let rpc = RpcClient::new_mock("sig_not_found".to_string());
+        let landing = send_and_confirm(&rpc, &signed_transaction(), Some(1000)).await.unwrap();
+
+        assert_eq!(landing.outcome, LandingOutcome::Expired {
+            last_valid_block_height: 1000,
+            block_height: 1234,
+        });
+        assert_eq!(serde_json::to_value(&landing).unwrap()["status"], json!("expired"));
+    }
+}
@@ -1,7 +1,23 @@
 pub mod config;
+pub mod confirmation;
 pub mod error;
+pub mod http;
+pub mod journal;
+pub mod jupiter;
+pub mod landing;
 pub mod mcp;
+pub mod mint;
+pub mod policy;
+pub mod priority_fee;
+pub mod prompts;
+pub mod quotes;
+pub mod resources;
 pub mod server;
+pub mod session;
+pub mod settlement;
+pub mod signing;
+pub mod simulation;
+pub mod tokens;
 pub mod tools;
 pub mod utils;
 
@@ -1,6 +1,6 @@
-use jup_mcp::{server::McpServer, Config};
+use jup_mcp::{config::Transport, http::run_http, server::McpServer, Config};
+use std::sync::Arc;
 use tracing::{error, info};
-use tracing_subscriber;
 
 #[tokio::main]
 async fn main() {
@@ -13,7 +13,10 @@ async fn main() {
         .init();
 
     // Load configuration
-    let config = match Config::from_env() {
+    let config = match Config::from_env().and_then(|mut config| {
+        config.apply_args(std::env::args().skip(1))?;
+        Ok(config)
+    }) {
         Ok(config) => {
             info!("Configuration loaded successfully");
             info!("Network: {:?}", config.network);
@@ -27,11 +30,23 @@ async fn main() {
     };
 
     // Create and run the MCP server
-    let server = McpServer::new(config);
+    let server = match McpServer::new(config) {
+        Ok(server) => server,
+        Err(e) => {
+            error!("Failed to create server: {}", e);
+            std::process::exit(1);
+        }
+    };
 
     info!("Starting Jupiter AG MCP Server...");
 
-    if let Err(e) = server.run_stdio().await {
+    let server = Arc::new(server);
+    let result = match server.config().transport {
+        Transport::Stdio => server.run_stdio().await,
+        Transport::Http => run_http(server).await,
+    };
+
+    if let Err(e) = result {
         error!("Server error: {}", e);
         std::process::exit(1);
     }
@@ -1,19 +1,59 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
 use uuid::Uuid;
 
+/// JSON-RPC request id, echoed back in the same form the client sent it
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RequestId {
+    /// Kept as a JSON number so floats and ids past `i64::MAX` round-trip
+    Number(serde_json::Number),
+    String(String),
+    /// Explicit `null`, also used when the request id could not be read
+    Null,
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestId::Number(n) => write!(f, "{}", n),
+            RequestId::String(s) => write!(f, "{}", s),
+            RequestId::Null => write!(f, "null"),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct McpRequest {
     pub jsonrpc: String,
-    pub id: String,
+    /// Absent for notifications, which must never be answered
+    #[serde(default, deserialize_with = "deserialize_present_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<RequestId>,
     pub method: String,
+    #[serde(default)]
     pub params: Option<Value>,
 }
 
+/// Keep `"id": null` distinct from a missing id
+fn deserialize_present_id<'de, D>(deserializer: D) -> std::result::Result<Option<RequestId>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    RequestId::deserialize(deserializer).map(Some)
+}
+
+impl McpRequest {
+    pub fn is_notification(&self) -> bool {
+        self.id.is_none()
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct McpResponse {
     pub jsonrpc: String,
-    pub id: String,
+    pub id: RequestId,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub result: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -32,6 +72,7 @@ pub struct McpError {
 pub struct Tool {
     pub name: String,
     pub description: String,
+    #[serde(rename = "inputSchema")]
     pub input_schema: ToolInputSchema,
 }
 
@@ -44,6 +85,64 @@ pub struct ToolInputSchema {
     pub required: Option<Vec<String>>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Resource {
+    pub uri: String,
+    pub name: String,
+    pub description: String,
+    #[serde(rename = "mimeType")]
+    pub mime_type: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResourceTemplate {
+    #[serde(rename = "uriTemplate")]
+    pub uri_template: String,
+    pub name: String,
+    pub description: String,
+    #[serde(rename = "mimeType")]
+    pub mime_type: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResourceContents {
+    pub uri: String,
+    #[serde(rename = "mimeType")]
+    pub mime_type: String,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResourceReadParams {
+    pub uri: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Prompt {
+    pub name: String,
+    pub description: String,
+    pub arguments: Vec<PromptArgument>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PromptArgument {
+    pub name: String,
+    pub description: String,
+    pub required: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PromptMessage {
+    pub role: String,
+    pub content: Content,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetPromptParams {
+    pub name: String,
+    pub arguments: Option<HashMap<String, String>>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ToolCallParams {
     pub name: String,
@@ -53,7 +152,9 @@ pub struct ToolCallParams {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ToolResponse {
     pub content: Vec<Content>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "structuredContent", skip_serializing_if = "Option::is_none")]
+    pub structured_content: Option<Value>,
+    #[serde(rename = "isError", skip_serializing_if = "Option::is_none")]
     pub is_error: Option<bool>,
 }
 
@@ -65,7 +166,7 @@ pub struct Content {
 }
 
 impl McpResponse {
-    pub fn success(id: String, result: Value) -> Self {
+    pub fn success(id: RequestId, result: Value) -> Self {
         Self {
             jsonrpc: "2.0".to_string(),
             id,
@@ -74,7 +175,7 @@ impl McpResponse {
         }
     }
     
-    pub fn error(id: String, code: i32, message: String) -> Self {
+    pub fn error(id: RequestId, code: i32, message: String) -> Self {
         Self {
             jsonrpc: "2.0".to_string(),
             id,
@@ -88,6 +189,23 @@ impl McpResponse {
     }
 }
 
+impl McpResponse {
+    /// Error reply for a line that is not a valid request: `-32700` when it is
+    /// not JSON at all, otherwise `-32600` carrying whatever id could be read
+    pub fn invalid_message(input: &str) -> Self {
+        match serde_json::from_str::<Value>(input) {
+            Ok(value) => {
+                let id = value
+                    .get("id")
+                    .and_then(|id| serde_json::from_value(id.clone()).ok())
+                    .unwrap_or(RequestId::Null);
+                Self::error(id, -32600, "Invalid Request".to_string())
+            }
+            Err(_) => Self::error(RequestId::Null, -32700, "Parse error".to_string()),
+        }
+    }
+}
+
 impl ToolResponse {
     pub fn text(text: String) -> Self {
         Self {
@@ -95,6 +213,7 @@ impl ToolResponse {
                 content_type: "text".to_string(),
                 text,
             }],
+            structured_content: None,
             is_error: None,
         }
     }
@@ -105,11 +224,84 @@ impl ToolResponse {
                 content_type: "text".to_string(),
                 text: format!("Error: {}", message),
             }],
+            structured_content: None,
             is_error: Some(true),
         }
     }
+    
+    /// Attach a machine-readable payload, mirrored as a JSON text item for
+    /// clients that do not read `structuredContent`
+    pub fn with_structured(mut self, value: Value) -> Self {
+        self.content.push(Content {
+            content_type: "text".to_string(),
+            text: value.to_string(),
+        });
+        self.structured_content = Some(value);
+        self
+    }
 }
 
 pub fn generate_request_id() -> String {
     Uuid::new_v4().to_string()
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_request_id_types() {
+        let numeric: McpRequest = serde_json::from_value(json!({
+            "jsonrpc": "2.0", "id": 7, "method": "tools/list"
+        })).unwrap();
+        assert_eq!(numeric.id, Some(RequestId::Number(7.into())));
+
+        let large: McpRequest = serde_json::from_value(json!({
+            "jsonrpc": "2.0", "id": u64::MAX, "method": "tools/list"
+        })).unwrap();
+        assert_eq!(large.id, Some(RequestId::Number(u64::MAX.into())));
+
+        let float: McpRequest = serde_json::from_str(r#"{"jsonrpc": "2.0", "id": 1.5, "method": "tools/list"}"#).unwrap();
+        let echoed = serde_json::to_string(&McpResponse::success(float.id.unwrap(), json!({}))).unwrap();
+        assert!(echoed.contains(r#""id":1.5"#));
+
+        let string: McpRequest = serde_json::from_value(json!({
+            "jsonrpc": "2.0", "id": "abc", "method": "tools/list"
+        })).unwrap();
+        assert_eq!(string.id, Some(RequestId::String("abc".to_string())));
+
+        let null: McpRequest = serde_json::from_value(json!({
+            "jsonrpc": "2.0", "id": null, "method": "tools/list"
+        })).unwrap();
+        assert_eq!(null.id, Some(RequestId::Null));
+    }
+
+    #[test]
+    fn test_notification_has_no_id() {
+        let notification: McpRequest = serde_json::from_value(json!({
+            "jsonrpc": "2.0", "method": "notifications/initialized"
+        })).unwrap();
+        assert!(notification.is_notification());
+    }
+
+    #[test]
+    fn test_invalid_message_keeps_readable_id() {
+        let response = McpResponse::invalid_message(r#"{"jsonrpc":"2.0","id":3}"#);
+        assert_eq!(response.id, RequestId::Number(3.into()));
+        assert_eq!(response.error.unwrap().code, -32600);
+
+        let response = McpResponse::invalid_message("not json");
+        assert_eq!(response.id, RequestId::Null);
+        assert_eq!(response.error.unwrap().code, -32700);
+    }
+
+    #[test]
+    fn test_response_echoes_id_type() {
+        let response = McpResponse::success(RequestId::Number(7.into()), json!({}));
+        assert_eq!(serde_json::to_value(&response).unwrap()["id"], json!(7));
+
+        let response = McpResponse::error(RequestId::Null, -32700, "Parse error".to_string());
+        assert_eq!(serde_json::to_value(&response).unwrap()["id"], Value::Null);
+    }
+}
@@ -0,0 +1,273 @@
+use crate::utils::TokenAmount;
+use crate::{JupiterMcpError, Result};
+use serde_json::{json, Value};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::extension::{
+    interest_bearing_mint::InterestBearingConfig,
+    permanent_delegate::PermanentDelegate,
+    transfer_fee::TransferFeeConfig,
+    transfer_hook::TransferHook,
+    BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+};
+use spl_token_2022::state::Mint;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A mint read from chain under either token program
+#[derive(Debug, Clone, PartialEq)]
+pub struct MintInfo {
+    pub address: Pubkey,
+    /// SPL Token or Token-2022 program id
+    pub program: Pubkey,
+    pub decimals: u8,
+    pub extensions: Vec<MintExtension>,
+}
+
+/// Token-2022 mint extensions that change what a holder sees or receives
+#[derive(Debug, Clone, PartialEq)]
+pub enum MintExtension {
+    /// Fee in effect for the current epoch
+    TransferFee { basis_points: u16, maximum_fee: u64 },
+    InterestBearing { rate_bps: i16, config: InterestBearingConfig },
+    TransferHook { program_id: Option<Pubkey> },
+    PermanentDelegate { delegate: Option<Pubkey> },
+    NonTransferable,
+    /// Any other extension, by name
+    Other(String),
+}
+
+impl MintInfo {
+    pub fn is_token_2022(&self) -> bool {
+        self.program == spl_token_2022::id()
+    }
+
+    /// The wallet's associated token account under the mint's program
+    pub fn associated_token_address(&self, wallet: &Pubkey) -> Pubkey {
+        get_associated_token_address_with_program_id(wallet, &self.address, &self.program)
+    }
+
+    /// Fee withheld when `amount` is transferred, if the mint charges one
+    pub fn transfer_fee(&self, amount: u64) -> Option<u64> {
+        self.extensions.iter().find_map(|extension| match extension {
+            MintExtension::TransferFee { basis_points, maximum_fee } => {
+                // Ceiling division, matching the token program
+                let fee = (amount as u128 * *basis_points as u128).div_ceil(10_000);
+                Some((fee as u64).min(*maximum_fee))
+            }
+            _ => None,
+        })
+    }
+
+    /// Human readable notes on each extension
+    pub fn describe_extensions(&self) -> Vec<String> {
+        self.extensions.iter().map(|extension| match extension {
+            MintExtension::TransferFee { basis_points, maximum_fee } => format!(
+                "transfer fee of {} bps (at most {}) withheld from every transfer",
+                basis_points,
+                TokenAmount::new(*maximum_fee, self.decimals)
+            ),
+            MintExtension::InterestBearing { rate_bps, .. } => format!(
+                "interest-bearing at {}{}.{:02}% a year",
+                if *rate_bps < 0 { "-" } else { "" },
+                rate_bps.unsigned_abs() / 100,
+                rate_bps.unsigned_abs() % 100
+            ),
+            MintExtension::TransferHook { program_id } => match program_id {
+                Some(program_id) => format!("transfers invoke hook program {}", program_id),
+                None => "transfer hook configured without a program".to_string(),
+            },
+            MintExtension::PermanentDelegate { delegate } => match delegate {
+                Some(delegate) => format!("permanent delegate {} can move or burn any holder's tokens", delegate),
+                None => "permanent delegate slot without a delegate".to_string(),
+            },
+            MintExtension::NonTransferable => "non-transferable".to_string(),
+            MintExtension::Other(name) => name.clone(),
+        }).collect()
+    }
+
+    /// Balance including accrued interest for interest-bearing mints
+    pub fn ui_amount_with_interest(&self, amount: u64) -> Option<String> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs() as i64;
+        self.extensions.iter().find_map(|extension| match extension {
+            MintExtension::InterestBearing { config, .. } => config.amount_to_ui_amount(amount, self.decimals, now),
+            _ => None,
+        })
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "program": self.program.to_string(),
+            "decimals": self.decimals,
+            "extensions": self.describe_extensions(),
+        })
+    }
+}
+
+/// Fetch a mint and decode it under whichever token program owns it
+pub async fn get_mint_info(rpc: &RpcClient, mint: &Pubkey) -> Result<MintInfo> {
+    let account = rpc.get_account(mint).await?;
+    let mut info = parse_mint(mint, &account.owner, &account.data, 0)?;
+
+    // Transfer fees can change per epoch, so pick the one in effect now
+    if info.extensions.iter().any(|extension| matches!(extension, MintExtension::TransferFee { .. })) {
+        let epoch = rpc.get_epoch_info().await?.epoch;
+        info = parse_mint(mint, &account.owner, &account.data, epoch)?;
+    }
+    Ok(info)
+}
+
+/// Decode mint account data owned by `program`
+pub fn parse_mint(address: &Pubkey, program: &Pubkey, data: &[u8], epoch: u64) -> Result<MintInfo> {
+    check_token_program(address, program)?;
+    let invalid = |e| JupiterMcpError::SolanaSdk(format!("Failed to parse mint {}: {}", address, e));
+
+    let state = StateWithExtensions::<Mint>::unpack(data).map_err(invalid)?;
+    let extensions = state
+        .get_extension_types()
+        .map_err(invalid)?
+        .into_iter()
+        .filter_map(|extension_type| read_extension(&state, extension_type, epoch))
+        .collect();
+
+    Ok(MintInfo {
+        address: *address,
+        program: *program,
+        decimals: state.base.decimals,
+        extensions,
+    })
+}
+
+fn check_token_program(address: &Pubkey, program: &Pubkey) -> Result<()> {
+    if *program == spl_token::id() || *program == spl_token_2022::id() {
+        Ok(())
+    } else {
+        Err(JupiterMcpError::InvalidInput(format!(
+            "Account {} is owned by {}, not a token program",
+            address, program
+        )))
+    }
+}
+
+fn read_extension(state: &StateWithExtensions<Mint>, extension_type: ExtensionType, epoch: u64) -> Option<MintExtension> {
+    let extension = match extension_type {
+        ExtensionType::TransferFeeConfig => {
+            let fee = state.get_extension::<TransferFeeConfig>().ok()?.get_epoch_fee(epoch);
+            MintExtension::TransferFee {
+                basis_points: u16::from(fee.transfer_fee_basis_points),
+                maximum_fee: u64::from(fee.maximum_fee),
+            }
+        }
+        ExtensionType::InterestBearingConfig => {
+            let config = *state.get_extension::<InterestBearingConfig>().ok()?;
+            MintExtension::InterestBearing {
+                rate_bps: i16::from(config.current_rate),
+                config,
+            }
+        }
+        ExtensionType::TransferHook => MintExtension::TransferHook {
+            program_id: state.get_extension::<TransferHook>().ok()?.program_id.into(),
+        },
+        ExtensionType::PermanentDelegate => MintExtension::PermanentDelegate {
+            delegate: state.get_extension::<PermanentDelegate>().ok()?.delegate.into(),
+        },
+        ExtensionType::NonTransferable => MintExtension::NonTransferable,
+        // Metadata and grouping do not affect balances or transfers
+        ExtensionType::Uninitialized
+        | ExtensionType::MetadataPointer
+        | ExtensionType::TokenMetadata
+        | ExtensionType::GroupPointer
+        | ExtensionType::TokenGroup
+        | ExtensionType::GroupMemberPointer
+        | ExtensionType::TokenGroupMember => return None,
+        other => MintExtension::Other(format!("{:?}", other)),
+    };
+    Some(extension)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::program_pack::Pack;
+    use spl_token_2022::extension::{transfer_fee::TransferFee, StateWithExtensionsMut};
+
+    fn classic_mint(decimals: u8) -> Vec<u8> {
+        let mut data = vec![0u8; spl_token::state::Mint::LEN];
+        spl_token::state::Mint::pack(spl_token::state::Mint {
+            decimals,
+            is_initialized: true,
+            ..Default::default()
+        }, &mut data).unwrap();
+        data
+    }
+
+    fn transfer_fee_mint(basis_points: u16, maximum_fee: u64) -> Vec<u8> {
+        let len = ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferFeeConfig]).unwrap();
+        let mut data = vec![0u8; len];
+        let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+        let fee = TransferFee {
+            epoch: 0.into(),
+            maximum_fee: maximum_fee.into(),
+            transfer_fee_basis_points: basis_points.into(),
+        };
+        let config = state.init_extension::<TransferFeeConfig>(true).unwrap();
+        config.older_transfer_fee = fee;
+        config.newer_transfer_fee = fee;
+        state.base = Mint {
+            decimals: 6,
+            is_initialized: true,
+            ..Default::default()
+        };
+        state.pack_base();
+        state.init_account_type().unwrap();
+        data
+    }
+
+    #[test]
+    fn test_parse_classic_mint() {
+        let mint = Pubkey::new_unique();
+        let info = parse_mint(&mint, &spl_token::id(), &classic_mint(9), 0).unwrap();
+        assert_eq!(info.decimals, 9);
+        assert!(!info.is_token_2022());
+        assert!(info.extensions.is_empty());
+        assert_eq!(info.transfer_fee(1_000), None);
+    }
+
+    #[test]
+    fn test_parse_transfer_fee_mint() {
+        let mint = Pubkey::new_unique();
+        let info = parse_mint(&mint, &spl_token_2022::id(), &transfer_fee_mint(150, 5_000), 0).unwrap();
+
+        assert!(info.is_token_2022());
+        assert_eq!(info.extensions, vec![MintExtension::TransferFee { basis_points: 150, maximum_fee: 5_000 }]);
+        assert_eq!(info.transfer_fee(1_000), Some(15));
+        assert_eq!(info.transfer_fee(1), Some(1));
+        assert_eq!(info.transfer_fee(1_000_000), Some(5_000));
+        assert!(info.describe_extensions()[0].contains("150 bps"));
+        assert_eq!(
+            info.associated_token_address(&Pubkey::default()),
+            get_associated_token_address_with_program_id(&Pubkey::default(), &mint, &spl_token_2022::id())
+        );
+    }
+
+    #[test]
+    fn test_describe_interest_rate() {
+        let describe = |rate_bps: i16| MintInfo {
+            address: Pubkey::new_unique(),
+            program: spl_token_2022::id(),
+            decimals: 6,
+            extensions: vec![MintExtension::InterestBearing { rate_bps, config: InterestBearingConfig::default() }],
+        }.describe_extensions().remove(0);
+
+        assert_eq!(describe(-50), "interest-bearing at -0.50% a year");
+        assert_eq!(describe(-1234), "interest-bearing at -12.34% a year");
+        assert_eq!(describe(5), "interest-bearing at 0.05% a year");
+        assert_eq!(describe(i16::MIN), "interest-bearing at -327.68% a year");
+    }
+
+    #[test]
+    fn test_non_token_program_is_rejected() {
+        let mint = Pubkey::new_unique();
+        assert!(parse_mint(&mint, &solana_sdk::system_program::id(), &classic_mint(6), 0).is_err());
+    }
+}
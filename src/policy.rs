@@ -0,0 +1,445 @@
+use crate::journal::JournalEntry;
+use crate::tools::get_quote::QuoteResponse;
+use crate::{JupiterMcpError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Length of a UTC day; daily caps and the journal agree on its boundary
+pub const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Limits applied to every swap before it is signed, loaded from a TOML or
+/// JSON file. Omitted fields are not enforced.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Policy {
+    pub max_slippage_bps: Option<u16>,
+    pub max_price_impact_pct: Option<f64>,
+    /// Most a swap transaction may pay in priority fees, in lamports
+    pub max_priority_fee_lamports: Option<u64>,
+    /// When non-empty, both sides of a swap must be in this list
+    #[serde(default)]
+    pub allowed_mints: Vec<String>,
+    #[serde(default)]
+    pub denied_mints: Vec<String>,
+    /// Input caps in base units, keyed by input mint. There is no cap on
+    /// notional (USD) value; cap each input mint instead.
+    #[serde(default)]
+    pub input_caps: HashMap<String, InputCap>,
+    #[serde(default)]
+    pub confirmation: ConfirmationPolicy,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InputCap {
+    pub max_per_swap: Option<u64>,
+    /// Rolling total per UTC day
+    pub max_per_day: Option<u64>,
+    /// Ask the user to approve swaps with a larger input
+    pub confirm_above: Option<u64>,
+}
+
+/// When a swap needs the user's explicit approval before it is signed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfirmationPolicy {
+    /// Ask for every swap
+    pub always: bool,
+    pub above_price_impact_pct: Option<f64>,
+    /// What to do when the client cannot show a confirmation
+    pub fallback: ConfirmationFallback,
+    /// How long to wait for the user's answer, never past the quote's expiry
+    pub timeout_secs: u64,
+}
+
+impl Default for ConfirmationPolicy {
+    fn default() -> Self {
+        Self {
+            always: false,
+            above_price_impact_pct: None,
+            fallback: ConfirmationFallback::Reject,
+            timeout_secs: 300,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfirmationFallback {
+    /// Refuse swaps that need confirmation
+    Reject,
+    /// Execute them without asking
+    Allow,
+}
+
+impl Policy {
+    /// Parse a policy file, picking JSON or TOML by extension
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        let is_json = path.extension().map(|ext| ext.eq_ignore_ascii_case("json")).unwrap_or(false);
+
+        let parsed = if is_json {
+            serde_json::from_str(&contents).map_err(|e| e.to_string())
+        } else {
+            toml::from_str(&contents).map_err(|e| e.to_string())
+        };
+        parsed.map_err(|e| JupiterMcpError::Environment(
+            format!("Invalid swap policy {}: {}", path.display(), e)
+        ))
+    }
+}
+
+/// A policy rule a swap would break
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PolicyViolation {
+    pub rule: &'static str,
+    pub message: String,
+}
+
+impl PolicyViolation {
+    fn new(rule: &'static str, message: String) -> Self {
+        Self { rule, message }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({ "policyViolation": self })
+    }
+}
+
+impl std::fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Swap blocked by policy rule '{}': {}", self.rule, self.message)
+    }
+}
+
+/// Enforces a `Policy` and tracks how much of each mint was swapped today
+#[derive(Debug, Default)]
+pub struct PolicyEngine {
+    policy: Policy,
+    /// Input spent per mint on the current UTC day
+    spent: Mutex<HashMap<String, DailySpend>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct DailySpend {
+    day: u64,
+    amount: u64,
+}
+
+/// Input counted against a daily cap by `PolicyEngine::reserve`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reservation {
+    mint: String,
+    day: u64,
+    amount: u64,
+}
+
+impl PolicyEngine {
+    pub fn new(policy: Policy) -> Self {
+        Self {
+            policy,
+            spent: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn policy(&self) -> &Policy {
+        &self.policy
+    }
+
+    /// Check a quote without counting it against the daily caps
+    pub fn check(&self, quote: &QuoteResponse) -> std::result::Result<(), PolicyViolation> {
+        self.check_on(quote, today())
+    }
+
+    /// Check a quote and reserve its input against the daily cap. Call
+    /// `release` if the swap does not go through.
+    pub fn reserve(&self, quote: &QuoteResponse) -> std::result::Result<Reservation, PolicyViolation> {
+        self.reserve_on(quote, today())
+    }
+
+    /// Count today's journaled swaps that may have spent their input, so a
+    /// restart does not reset the daily caps
+    pub fn restore(&self, entries: &[JournalEntry]) {
+        self.restore_on(entries, today())
+    }
+
+    /// Check the priority fee a built swap transaction would pay
+    pub fn check_priority_fee(&self, lamports: u64) -> std::result::Result<(), PolicyViolation> {
+        match self.policy.max_priority_fee_lamports {
+            Some(max) if lamports > max => Err(PolicyViolation::new("max_priority_fee_lamports", format!(
+                "priority fee of up to {} lamports exceeds the maximum of {} lamports", lamports, max
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Why the swap needs the user's approval, if it does
+    pub fn confirmation_reason(&self, quote: &QuoteResponse) -> Option<String> {
+        let confirmation = &self.policy.confirmation;
+        if confirmation.always {
+            return Some("every swap requires approval".to_string());
+        }
+
+        if let Some(max) = confirmation.above_price_impact_pct {
+            let impact = quote.price_impact_pct.parse::<f64>().map(f64::abs).unwrap_or(f64::INFINITY);
+            if impact > max {
+                return Some(format!("price impact of {}% is above {}%", quote.price_impact_pct, max));
+            }
+        }
+
+        let threshold = self.policy.input_caps.get(&quote.input_mint)?.confirm_above?;
+        let amount = max_input(quote).unwrap_or(u64::MAX);
+        (amount > threshold).then(|| format!("input of {} is above {}", amount, threshold))
+    }
+
+    /// Return a reservation made by `reserve` for a swap that was never
+    /// sent. One made on an earlier day no longer counts, so it is dropped.
+    pub fn release(&self, reservation: Reservation) {
+        let mut spent = self.spent.lock().unwrap();
+        if let Some(entry) = spent.get_mut(&reservation.mint).filter(|entry| entry.day == reservation.day) {
+            entry.amount = entry.amount.saturating_sub(reservation.amount);
+        }
+    }
+
+    fn restore_on(&self, entries: &[JournalEntry], day: u64) {
+        let mut spent = self.spent.lock().unwrap();
+        for entry in entries.iter().filter(|entry| entry.timestamp / SECONDS_PER_DAY == day && entry.may_have_spent()) {
+            let Ok(amount) = max_input(&entry.quote) else { continue };
+            let current = spent_on(&spent, &entry.quote.input_mint, day);
+            spent.insert(entry.quote.input_mint.clone(), DailySpend { day, amount: current.saturating_add(amount) });
+        }
+    }
+
+    fn check_on(&self, quote: &QuoteResponse, day: u64) -> std::result::Result<(), PolicyViolation> {
+        let spent = spent_on(&self.spent.lock().unwrap(), &quote.input_mint, day);
+        self.evaluate(quote, spent)
+    }
+
+    /// Apply every rule given how much of the input mint was already swapped today
+    fn evaluate(&self, quote: &QuoteResponse, spent_today: u64) -> std::result::Result<(), PolicyViolation> {
+        let policy = &self.policy;
+
+        for mint in [&quote.input_mint, &quote.output_mint] {
+            if policy.denied_mints.contains(mint) {
+                return Err(PolicyViolation::new("denied_mints", format!("mint {} is on the deny list", mint)));
+            }
+            if !policy.allowed_mints.is_empty() && !policy.allowed_mints.contains(mint) {
+                return Err(PolicyViolation::new("allowed_mints", format!("mint {} is not on the allow list", mint)));
+            }
+        }
+
+        if let Some(max) = policy.max_slippage_bps {
+            if quote.slippage_bps > max {
+                return Err(PolicyViolation::new("max_slippage_bps", format!(
+                    "slippage of {} bps exceeds the maximum of {} bps", quote.slippage_bps, max
+                )));
+            }
+        }
+
+        if let Some(max) = policy.max_price_impact_pct {
+            let impact: f64 = quote.price_impact_pct.parse().map_err(|_| PolicyViolation::new(
+                "max_price_impact_pct",
+                format!("quote has an unreadable price impact '{}'", quote.price_impact_pct),
+            ))?;
+            if impact.abs() > max {
+                return Err(PolicyViolation::new("max_price_impact_pct", format!(
+                    "price impact of {}% exceeds the maximum of {}%", impact, max
+                )));
+            }
+        }
+
+        if let Some(cap) = policy.input_caps.get(&quote.input_mint) {
+            let amount = max_input(quote)?;
+
+            if let Some(max) = cap.max_per_swap {
+                if amount > max {
+                    return Err(PolicyViolation::new("max_per_swap", format!(
+                        "input of {} exceeds the per-swap cap of {} for {}", amount, max, quote.input_mint
+                    )));
+                }
+            }
+
+            if let Some(max) = cap.max_per_day {
+                if spent_today.saturating_add(amount) > max {
+                    return Err(PolicyViolation::new("max_per_day", format!(
+                        "input of {} on top of {} already swapped today exceeds the daily cap of {} for {}",
+                        amount, spent_today, max, quote.input_mint
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn reserve_on(&self, quote: &QuoteResponse, day: u64) -> std::result::Result<Reservation, PolicyViolation> {
+        // Hold the lock across check and update so concurrent swaps cannot
+        // both fit under the same remaining daily allowance
+        let mut spent = self.spent.lock().unwrap();
+        let current = spent_on(&spent, &quote.input_mint, day);
+        self.evaluate(quote, current)?;
+
+        let amount = max_input(quote)?;
+        spent.insert(quote.input_mint.clone(), DailySpend { day, amount: current.saturating_add(amount) });
+        Ok(Reservation { mint: quote.input_mint.clone(), day, amount })
+    }
+}
+
+fn spent_on(spent: &HashMap<String, DailySpend>, mint: &str, day: u64) -> u64 {
+    spent
+        .get(mint)
+        .filter(|entry| entry.day == day)
+        .map(|entry| entry.amount)
+        .unwrap_or(0)
+}
+
+/// Most input the swap can take: the quoted amount for ExactIn, the
+/// slippage-adjusted threshold for ExactOut
+fn max_input(quote: &QuoteResponse) -> std::result::Result<u64, PolicyViolation> {
+    let amount = if quote.swap_mode == "ExactOut" {
+        &quote.other_amount_threshold
+    } else {
+        &quote.in_amount
+    };
+    amount.parse().map_err(|_| PolicyViolation::new(
+        "max_per_swap",
+        format!("quote has an unreadable input amount '{}'", amount),
+    ))
+}
+
+fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() / SECONDS_PER_DAY)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::tools::get_quote::test_quotes::{sample_quote, SOL, USDC};
+
+    fn quote(in_amount: u64, slippage_bps: u16, price_impact_pct: &str) -> QuoteResponse {
+        QuoteResponse {
+            in_amount: in_amount.to_string(),
+            slippage_bps,
+            price_impact_pct: price_impact_pct.to_string(),
+            ..sample_quote()
+        }
+    }
+
+    #[test]
+    fn test_load_toml_policy() {
+        let policy: Policy = toml::from_str(&format!(r#"
+            max_slippage_bps = 100
+            max_price_impact_pct = 1.5
+            denied_mints = ["{USDC}"]
+
+            [input_caps."{SOL}"]
+            max_per_swap = 1000
+            max_per_day = 2500
+        "#)).unwrap();
+
+        assert_eq!(policy.max_slippage_bps, Some(100));
+        assert_eq!(policy.input_caps[SOL].max_per_day, Some(2500));
+        assert!(policy.allowed_mints.is_empty());
+    }
+
+    #[test]
+    fn test_rules_are_named() {
+        let engine = PolicyEngine::new(Policy {
+            max_slippage_bps: Some(50),
+            max_price_impact_pct: Some(1.0),
+            input_caps: HashMap::from([(SOL.to_string(), InputCap { max_per_swap: Some(1000), ..InputCap::default() })]),
+            ..Policy::default()
+        });
+
+        assert!(engine.check(&quote(1000, 50, "0.5")).is_ok());
+        assert_eq!(engine.check(&quote(1000, 51, "0.5")).unwrap_err().rule, "max_slippage_bps");
+        assert_eq!(engine.check(&quote(1000, 50, "1.2")).unwrap_err().rule, "max_price_impact_pct");
+        assert_eq!(engine.check(&quote(1001, 50, "0.5")).unwrap_err().rule, "max_per_swap");
+
+        let fees = PolicyEngine::new(Policy { max_priority_fee_lamports: Some(10_000), ..Policy::default() });
+        assert!(fees.check_priority_fee(10_000).is_ok());
+        assert_eq!(fees.check_priority_fee(10_001).unwrap_err().rule, "max_priority_fee_lamports");
+
+        let deny = PolicyEngine::new(Policy { denied_mints: vec![USDC.to_string()], ..Policy::default() });
+        assert_eq!(deny.check(&quote(1, 0, "0")).unwrap_err().rule, "denied_mints");
+
+        let allow = PolicyEngine::new(Policy { allowed_mints: vec![SOL.to_string()], ..Policy::default() });
+        assert_eq!(allow.check(&quote(1, 0, "0")).unwrap_err().rule, "allowed_mints");
+    }
+
+    #[test]
+    fn test_confirmation_thresholds() {
+        let engine = PolicyEngine::new(toml::from_str(&format!(r#"
+            [confirmation]
+            above_price_impact_pct = 1.0
+
+            [input_caps."{SOL}"]
+            confirm_above = 1000
+        "#)).unwrap());
+
+        assert_eq!(engine.policy().confirmation.fallback, ConfirmationFallback::Reject);
+        assert!(engine.confirmation_reason(&quote(1000, 50, "0.5")).is_none());
+        assert!(engine.confirmation_reason(&quote(1001, 50, "0.5")).unwrap().contains("above 1000"));
+        assert!(engine.confirmation_reason(&quote(10, 50, "2")).unwrap().contains("price impact"));
+    }
+
+    #[test]
+    fn test_daily_cap_accumulates_and_resets() {
+        let engine = PolicyEngine::new(Policy {
+            input_caps: HashMap::from([(SOL.to_string(), InputCap { max_per_day: Some(2500), ..InputCap::default() })]),
+            ..Policy::default()
+        });
+
+        assert!(engine.reserve_on(&quote(1000, 0, "0"), 1).is_ok());
+        let reservation = engine.reserve_on(&quote(1000, 0, "0"), 1).unwrap();
+        assert_eq!(engine.reserve_on(&quote(1000, 0, "0"), 1).unwrap_err().rule, "max_per_day");
+
+        // A swap that was never sent hands its allowance back
+        engine.release(reservation.clone());
+        assert!(engine.check_on(&quote(1000, 0, "0"), 1).is_ok());
+
+        // The next day starts from zero, and yesterday's reservation does not eat into it
+        assert!(engine.reserve_on(&quote(2500, 0, "0"), 2).is_ok());
+        engine.release(reservation);
+        assert_eq!(engine.check_on(&quote(1, 0, "0"), 2).unwrap_err().rule, "max_per_day");
+    }
+
+    #[test]
+    fn test_restore_counts_todays_sent_swaps() {
+        use crate::journal::{PolicyDecision, SwapStatus};
+
+        let engine = PolicyEngine::new(Policy {
+            input_caps: HashMap::from([(SOL.to_string(), InputCap { max_per_day: Some(2500), ..InputCap::default() })]),
+            ..Policy::default()
+        });
+        let entry = |status, timestamp, signature: Option<&str>| {
+            let mut entry = JournalEntry::new("quote", &quote(1000, 0, "0"), PolicyDecision::default(), status);
+            entry.timestamp = timestamp;
+            entry.signature = signature.map(str::to_string);
+            entry
+        };
+        let day = 3;
+        let today = day * SECONDS_PER_DAY + 60;
+
+        engine.restore_on(&[
+            entry(SwapStatus::Confirmed, today, Some("sig")),
+            entry(SwapStatus::Error, today, Some("sig")),
+            // Never sent, expired, or from another day
+            entry(SwapStatus::Error, today, None),
+            entry(SwapStatus::Rejected, today, None),
+            entry(SwapStatus::Expired, today, Some("sig")),
+            entry(SwapStatus::Confirmed, today - SECONDS_PER_DAY, Some("sig")),
+        ], day);
+
+        assert!(engine.check_on(&quote(500, 0, "0"), day).is_ok());
+        assert_eq!(engine.check_on(&quote(501, 0, "0"), day).unwrap_err().rule, "max_per_day");
+    }
+}
@@ -0,0 +1,225 @@
+use crate::{JupiterMcpError, Result};
+use serde::{Deserialize, Serialize};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    compute_budget::{self, ComputeBudgetInstruction},
+    message::VersionedMessage,
+    pubkey::Pubkey,
+    transaction::VersionedTransaction,
+};
+
+/// `getRecentPrioritizationFees` accepts at most this many accounts
+const MAX_FEE_ACCOUNTS: usize = 128;
+
+/// Discriminant of `ComputeBudgetInstruction::SetComputeUnitLimit`
+const SET_COMPUTE_UNIT_LIMIT: u8 = 2;
+
+/// Discriminant of `ComputeBudgetInstruction::SetComputeUnitPrice`
+const SET_COMPUTE_UNIT_PRICE: u8 = 3;
+
+/// Units the runtime grants each instruction when no limit is set
+const DEFAULT_INSTRUCTION_COMPUTE_UNITS: u64 = 200_000;
+
+/// Largest compute unit limit a transaction can have
+const MAX_COMPUTE_UNITS: u64 = 1_400_000;
+
+/// How `execute_swap` prices compute units
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PriorityFee {
+    /// `"auto"`: let Jupiter estimate the fee
+    Auto(AutoFee),
+    /// `{"microLamports": n}`: a fixed compute unit price
+    Fixed {
+        #[serde(rename = "microLamports")]
+        micro_lamports: u64,
+    },
+    /// `{"percentile": p}`: a percentile of recent fees paid on the route's accounts
+    Percentile { percentile: u8 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AutoFee {
+    Auto,
+}
+
+impl PriorityFee {
+    /// Compute unit price in microLamports, or `None` when Jupiter should pick it
+    pub async fn resolve(&self, rpc: &RpcClient, accounts: &[Pubkey]) -> Result<Option<u64>> {
+        match *self {
+            PriorityFee::Auto(_) => Ok(None),
+            PriorityFee::Fixed { micro_lamports } => Ok(Some(micro_lamports)),
+            PriorityFee::Percentile { percentile } => {
+                if !(1..=100).contains(&percentile) {
+                    return Err(JupiterMcpError::InvalidInput(format!(
+                        "priorityFee percentile must be between 1 and 100, got {}",
+                        percentile
+                    )));
+                }
+
+                let accounts = &accounts[..accounts.len().min(MAX_FEE_ACCOUNTS)];
+                let fees: Vec<u64> = rpc
+                    .get_recent_prioritization_fees(accounts)
+                    .await?
+                    .into_iter()
+                    .map(|fee| fee.prioritization_fee)
+                    .collect();
+
+                Ok(Some(fee_percentile(fees, percentile)))
+            }
+        }
+    }
+}
+
+/// Nearest-rank percentile of the fees, 0 when there are none
+pub fn fee_percentile(mut fees: Vec<u64>, percentile: u8) -> u64 {
+    if fees.is_empty() {
+        return 0;
+    }
+    fees.sort_unstable();
+    let rank = (fees.len() * percentile as usize).div_ceil(100).max(1);
+    fees[rank.min(fees.len()) - 1]
+}
+
+/// Most a transaction can pay in priority fees, in lamports: its compute
+/// unit price times its compute unit limit
+pub fn priority_fee_lamports(transaction: &VersionedTransaction) -> u64 {
+    let keys = transaction.message.static_account_keys();
+    let mut price: u64 = 0;
+    let mut limit = None;
+    let mut other_instructions: u64 = 0;
+
+    for instruction in transaction.message.instructions() {
+        if keys.get(instruction.program_id_index as usize) != Some(&compute_budget::id()) {
+            other_instructions += 1;
+            continue;
+        }
+        match instruction.data.split_first() {
+            Some((&SET_COMPUTE_UNIT_LIMIT, rest)) => {
+                limit = rest.get(..4).and_then(|bytes| bytes.try_into().ok()).map(u32::from_le_bytes);
+            }
+            Some((&SET_COMPUTE_UNIT_PRICE, rest)) => {
+                price = rest.get(..8).and_then(|bytes| bytes.try_into().ok()).map(u64::from_le_bytes).unwrap_or(0);
+            }
+            _ => {}
+        }
+    }
+
+    let limit = match limit {
+        Some(units) => (units as u64).min(MAX_COMPUTE_UNITS),
+        None => (other_instructions * DEFAULT_INSTRUCTION_COMPUTE_UNITS).min(MAX_COMPUTE_UNITS),
+    };
+    let lamports = (price as u128 * limit as u128).div_ceil(1_000_000);
+    u64::try_from(lamports).unwrap_or(u64::MAX)
+}
+
+/// Overwrite the compute unit limit of an unsigned transaction
+pub fn set_compute_unit_limit(transaction: &mut VersionedTransaction, units: u32) -> Result<()> {
+    let data = ComputeBudgetInstruction::set_compute_unit_limit(units).data;
+    let keys = transaction.message.static_account_keys().to_vec();
+    let instructions = match &mut transaction.message {
+        VersionedMessage::Legacy(message) => &mut message.instructions,
+        VersionedMessage::V0(message) => &mut message.instructions,
+    };
+
+    let instruction = instructions
+        .iter_mut()
+        .find(|instruction| {
+            keys.get(instruction.program_id_index as usize) == Some(&compute_budget::id())
+                && instruction.data.first() == Some(&SET_COMPUTE_UNIT_LIMIT)
+        })
+        .ok_or_else(|| JupiterMcpError::SolanaSdk(
+            "Swap transaction has no compute unit limit instruction to adjust".to_string()
+        ))?;
+
+    instruction.data = data;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use solana_sdk::{hash::Hash, message::v0, system_instruction};
+
+    #[test]
+    fn test_priority_fee_modes() {
+        let auto: PriorityFee = serde_json::from_value(json!("auto")).unwrap();
+        let fixed: PriorityFee = serde_json::from_value(json!({"microLamports": 5000})).unwrap();
+        let percentile: PriorityFee = serde_json::from_value(json!({"percentile": 75})).unwrap();
+
+        assert_eq!(auto, PriorityFee::Auto(AutoFee::Auto));
+        assert_eq!(fixed, PriorityFee::Fixed { micro_lamports: 5000 });
+        assert_eq!(percentile, PriorityFee::Percentile { percentile: 75 });
+        assert!(serde_json::from_value::<PriorityFee>(json!("high")).is_err());
+    }
+
+    #[test]
+    fn test_fee_percentile() {
+        let fees = vec![500, 0, 100, 300, 200, 400, 0, 0, 1000, 700];
+
+        assert_eq!(fee_percentile(fees.clone(), 50), 200);
+        assert_eq!(fee_percentile(fees.clone(), 90), 700);
+        assert_eq!(fee_percentile(fees.clone(), 100), 1000);
+        assert_eq!(fee_percentile(fees, 1), 0);
+        assert_eq!(fee_percentile(Vec::new(), 75), 0);
+    }
+
+    #[test]
+    fn test_priority_fee_lamports() {
+        let payer = Pubkey::new_unique();
+        let transfer = system_instruction::transfer(&payer, &Pubkey::new_unique(), 1);
+        let transaction = |instructions: &[solana_sdk::instruction::Instruction]| VersionedTransaction {
+            signatures: Vec::new(),
+            message: VersionedMessage::V0(
+                v0::Message::try_compile(&payer, instructions, &[], Hash::new_unique()).unwrap(),
+            ),
+        };
+
+        let limited = transaction(&[
+            ComputeBudgetInstruction::set_compute_unit_limit(300_000),
+            ComputeBudgetInstruction::set_compute_unit_price(2_500),
+            transfer.clone(),
+        ]);
+        assert_eq!(priority_fee_lamports(&limited), 750);
+
+        // Without a limit, each other instruction may use the default units
+        let unlimited = transaction(&[
+            ComputeBudgetInstruction::set_compute_unit_price(1_000_001),
+            transfer.clone(),
+            transfer.clone(),
+        ]);
+        assert_eq!(priority_fee_lamports(&unlimited), 400_001);
+
+        assert_eq!(priority_fee_lamports(&transaction(&[transfer])), 0);
+    }
+
+    #[test]
+    fn test_set_compute_unit_limit() {
+        let payer = Pubkey::new_unique();
+        let instructions = [
+            ComputeBudgetInstruction::set_compute_unit_limit(1_400_000),
+            ComputeBudgetInstruction::set_compute_unit_price(1000),
+            system_instruction::transfer(&payer, &Pubkey::new_unique(), 1),
+        ];
+        let message = v0::Message::try_compile(&payer, &instructions, &[], Hash::new_unique()).unwrap();
+        let mut transaction = VersionedTransaction {
+            signatures: Vec::new(),
+            message: VersionedMessage::V0(message),
+        };
+
+        set_compute_unit_limit(&mut transaction, 200_000).unwrap();
+
+        let compiled = transaction.message.instructions();
+        assert_eq!(compiled[0].data, ComputeBudgetInstruction::set_compute_unit_limit(200_000).data);
+        assert_eq!(compiled[1].data, instructions[1].data);
+
+        let message = v0::Message::try_compile(&payer, &instructions[2..], &[], Hash::new_unique()).unwrap();
+        let mut transaction = VersionedTransaction {
+            signatures: Vec::new(),
+            message: VersionedMessage::V0(message),
+        };
+        assert!(set_compute_unit_limit(&mut transaction, 200_000).is_err());
+    }
+}
@@ -0,0 +1,134 @@
+use crate::mcp::{Content, Prompt, PromptArgument, PromptMessage};
+use crate::{JupiterMcpError, Result};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+pub fn list() -> Vec<Prompt> {
+    vec![
+        Prompt {
+            name: "safe_swap".to_string(),
+            description: "Swap an amount of one token into another, checking the balance and getting explicit user approval of the quote first".to_string(),
+            arguments: vec![
+                argument("amount", "Amount of the input token to swap, in its smallest unit", true),
+                argument("inputMint", "Mint address or symbol of the token to swap from", true),
+                argument("outputMint", "Mint address or symbol of the token to swap to", true),
+                argument("slippageBps", "Maximum slippage in basis points (default 50)", false),
+            ],
+        },
+        Prompt {
+            name: "review_wallet".to_string(),
+            description: "Review a wallet's holdings and walk through any rebalancing swaps the user asks for".to_string(),
+            arguments: vec![
+                argument("walletAddress", "Wallet to review (defaults to the server wallet)", false),
+            ],
+        },
+    ]
+}
+
+/// Render a prompt with the given arguments
+pub fn get(name: &str, arguments: &HashMap<String, String>) -> Result<Value> {
+    let (description, text) = match name {
+        "safe_swap" => {
+            let amount = required(arguments, "amount")?;
+            let input_mint = required(arguments, "inputMint")?;
+            let output_mint = required(arguments, "outputMint")?;
+            let slippage_bps = arguments.get("slippageBps").map(String::as_str).unwrap_or("50");
+            (
+                "Guided swap with balance check and confirmation",
+                format!(
+                    "I want to swap {amount} (smallest units) of {input_mint} into {output_mint} \
+                    with at most {slippage_bps} bps slippage. Follow these steps in order and do not skip any:\n\n\
+                    1. Call get_token_balance without a walletAddress, so it checks the server wallet that \
+                    signs the swap, with tokenMint {input_mint} (omit tokenMint if it is native SOL). \
+                    Stop if the balance is below {amount}.\n\
+                    2. Call get_quote with inputMint {input_mint}, outputMint {output_mint}, \
+                    amount {amount}, amountUnits \"base\" and slippageBps {slippage_bps}.\n\
+                    3. Show me the amounts I send and receive, the price impact and the route from the quote, \
+                    warn me if the price impact is above 1%, and ask me to confirm. \
+                    Do not continue unless I explicitly approve.\n\
+                    4. Only after I approve, call execute_swap with the quoteId from step 2. \
+                    If the quote has expired, get a fresh quote and ask me again.\n\
+                    5. Report the signature and explorer link, or the error if the swap failed."
+                ),
+            )
+        }
+        "review_wallet" => {
+            let wallet = arguments
+                .get("walletAddress")
+                .map(|address| format!("wallet {}", address))
+                .unwrap_or_else(|| "the server wallet".to_string());
+            (
+                "Wallet review with optional rebalancing",
+                format!(
+                    "Review the holdings of {wallet}:\n\n\
+                    1. Call get_token_balance for its SOL balance and read the \
+                    solana://wallet/{{address}}/balances resource for its token balances.\n\
+                    2. Summarise the holdings and point out anything notable, such as a SOL balance too low to pay fees.\n\
+                    3. If I ask to rebalance, handle each swap one at a time: check the balance with \
+                    get_token_balance, call get_quote, show me the amounts, price impact and route, \
+                    and wait for my explicit approval.\n\
+                    4. Call execute_swap with the quoteId from get_quote only after I approve, \
+                    then report the signature before moving to the next swap."
+                ),
+            )
+        }
+        _ => return Err(JupiterMcpError::InvalidInput(format!("Unknown prompt: {}", name))),
+    };
+
+    let messages = vec![PromptMessage {
+        role: "user".to_string(),
+        content: Content {
+            content_type: "text".to_string(),
+            text,
+        },
+    }];
+
+    Ok(json!({
+        "description": description,
+        "messages": messages,
+    }))
+}
+
+fn argument(name: &str, description: &str, required: bool) -> PromptArgument {
+    PromptArgument {
+        name: name.to_string(),
+        description: description.to_string(),
+        required,
+    }
+}
+
+fn required<'a>(arguments: &'a HashMap<String, String>, name: &str) -> Result<&'a str> {
+    arguments
+        .get(name)
+        .map(String::as_str)
+        .ok_or_else(|| JupiterMcpError::InvalidInput(format!("Missing prompt argument: {}", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_safe_swap_walks_through_every_step() {
+        let arguments = HashMap::from([
+            ("amount".to_string(), "1000000".to_string()),
+            ("inputMint".to_string(), "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string()),
+            ("outputMint".to_string(), "So11111111111111111111111111111111111111112".to_string()),
+        ]);
+
+        let prompt = get("safe_swap", &arguments).unwrap();
+        let text = prompt["messages"][0]["content"]["text"].as_str().unwrap();
+
+        let steps = ["get_token_balance without a walletAddress", "get_quote", "confirm", "execute_swap"];
+        let positions: Vec<usize> = steps.iter().map(|step| text.find(step).unwrap()).collect();
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(text.contains("slippageBps 50"));
+    }
+
+    #[test]
+    fn test_missing_argument_is_rejected() {
+        assert!(get("safe_swap", &HashMap::new()).is_err());
+        assert!(get("unknown", &HashMap::new()).is_err());
+        assert!(get("review_wallet", &HashMap::new()).is_ok());
+    }
+}
@@ -0,0 +1,122 @@
+use crate::mcp::generate_request_id;
+use crate::tools::get_quote::QuoteResponse;
+use crate::{JupiterMcpError, Result};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+struct StoredQuote {
+    quote: QuoteResponse,
+    expires_at: Instant,
+}
+
+/// Quotes returned by `get_quote`, kept server-side under a generated id so
+/// `execute_swap` only ever acts on amounts Jupiter actually quoted
+#[derive(Debug)]
+pub struct QuoteStore {
+    ttl: Duration,
+    quotes: Mutex<HashMap<String, StoredQuote>>,
+}
+
+impl QuoteStore {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            quotes: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Store a quote and return the id it can be looked up by
+    pub fn insert(&self, quote: QuoteResponse) -> String {
+        let id = generate_request_id();
+        let now = Instant::now();
+        let mut quotes = self.quotes.lock().unwrap();
+        // Keep recently expired quotes around so lookups can say why they failed
+        quotes.retain(|_, stored| stored.expires_at + self.ttl > now);
+        quotes.insert(id.clone(), StoredQuote {
+            quote,
+            expires_at: now + self.ttl,
+        });
+        id
+    }
+
+    /// Look up a live quote
+    pub fn get(&self, id: &str) -> Result<QuoteResponse> {
+        let quotes = self.quotes.lock().unwrap();
+        let stored = Self::live(&quotes, id)?;
+        Ok(stored.quote.clone())
+    }
+
+    /// Remove and return a live quote so it cannot be executed twice
+    pub fn take(&self, id: &str) -> Result<QuoteResponse> {
+        let mut quotes = self.quotes.lock().unwrap();
+        Self::live(&quotes, id)?;
+        Ok(quotes.remove(id).map(|stored| stored.quote).unwrap())
+    }
+
+    /// How much longer a live quote can be executed
+    pub fn remaining(&self, id: &str) -> Result<Duration> {
+        let quotes = self.quotes.lock().unwrap();
+        let stored = Self::live(&quotes, id)?;
+        Ok(stored.expires_at.saturating_duration_since(Instant::now()))
+    }
+
+    /// Ids of every quote that has not expired
+    pub fn ids(&self) -> Vec<String> {
+        let now = Instant::now();
+        self.quotes
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, stored)| stored.expires_at > now)
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+
+    fn live<'a>(quotes: &'a HashMap<String, StoredQuote>, id: &str) -> Result<&'a StoredQuote> {
+        let stored = quotes.get(id).ok_or_else(|| JupiterMcpError::InvalidInput(format!(
+            "Unknown quote id '{}'; request a quote with get_quote first", id
+        )))?;
+
+        if stored.expires_at <= Instant::now() {
+            return Err(JupiterMcpError::InvalidInput(format!(
+                "Quote '{}' has expired; request a new quote with get_quote", id
+            )));
+        }
+        Ok(stored)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::get_quote::test_quotes::sample_quote;
+
+    #[test]
+    fn test_take_is_single_use() {
+        let store = QuoteStore::new(Duration::from_secs(60));
+        let id = store.insert(sample_quote());
+
+        assert_eq!(store.get(&id).unwrap().in_amount, "1000000000");
+        assert!(store.remaining(&id).unwrap() <= Duration::from_secs(60));
+        assert!(store.take(&id).is_ok());
+        assert!(store.take(&id).unwrap_err().to_string().contains("Unknown quote id"));
+    }
+
+    #[test]
+    fn test_expired_quote_is_refused() {
+        let store = QuoteStore::new(Duration::ZERO);
+        let id = store.insert(sample_quote());
+
+        assert!(store.remaining(&id).is_err());
+        assert!(store.take(&id).unwrap_err().to_string().contains("expired"));
+        assert!(store.ids().is_empty());
+    }
+
+    #[test]
+    fn test_unknown_quote_is_refused() {
+        let store = QuoteStore::new(Duration::from_secs(60));
+        assert!(store.get("made-up").is_err());
+    }
+}
@@ -0,0 +1,172 @@
+use crate::mcp::{Resource, ResourceContents, ResourceTemplate};
+use crate::tools::get_balance::get_token_holdings;
+use crate::tools::ToolContext;
+use crate::utils::{load_wallet, parse_pubkey, TokenAmount, SOL_DECIMALS};
+use crate::{JupiterMcpError, Result};
+use serde_json::{json, Value};
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Signature, Signer},
+};
+use solana_transaction_status::UiTransactionEncoding;
+use std::str::FromStr;
+
+const JSON_MIME_TYPE: &str = "application/json";
+
+/// A parsed resource URI
+#[derive(Debug, PartialEq, Eq)]
+pub enum ResourceUri {
+    /// `solana://wallet/{address}/balances`
+    WalletBalances(Pubkey),
+    /// `jupiter://quote/{id}`
+    Quote(String),
+    /// `solana://tx/{signature}`
+    Transaction(Signature),
+}
+
+impl FromStr for ResourceUri {
+    type Err = JupiterMcpError;
+
+    fn from_str(uri: &str) -> Result<Self> {
+        let not_found = || JupiterMcpError::InvalidInput(format!("Unknown resource: {}", uri));
+
+        if let Some(path) = uri.strip_prefix("solana://wallet/") {
+            let address = path.strip_suffix("/balances").ok_or_else(not_found)?;
+            return Ok(ResourceUri::WalletBalances(parse_pubkey(address)?));
+        }
+        if let Some(signature) = uri.strip_prefix("solana://tx/") {
+            let signature = Signature::from_str(signature).map_err(|e| {
+                JupiterMcpError::InvalidInput(format!("Invalid signature '{}': {}", signature, e))
+            })?;
+            return Ok(ResourceUri::Transaction(signature));
+        }
+        if let Some(id) = uri.strip_prefix("jupiter://quote/") {
+            if !id.is_empty() {
+                return Ok(ResourceUri::Quote(id.to_string()));
+            }
+        }
+        Err(not_found())
+    }
+}
+
+/// Concrete resources: the server wallet's balances and every stored quote
+pub fn list(ctx: &ToolContext) -> Vec<Resource> {
+    let mut resources = Vec::new();
+
+    if let Ok(wallet) = load_wallet(&ctx.config) {
+        resources.push(Resource {
+            uri: format!("solana://wallet/{}/balances", wallet.pubkey()),
+            name: "Server wallet balances".to_string(),
+            description: "SOL and SPL token balances of the wallet that signs swaps".to_string(),
+            mime_type: JSON_MIME_TYPE.to_string(),
+        });
+    }
+
+    for id in ctx.quotes.ids() {
+        resources.push(Resource {
+            uri: format!("jupiter://quote/{}", id),
+            name: format!("Quote {}", id),
+            description: "A quote returned by get_quote".to_string(),
+            mime_type: JSON_MIME_TYPE.to_string(),
+        });
+    }
+
+    resources
+}
+
+pub fn templates() -> Vec<ResourceTemplate> {
+    vec![
+        ResourceTemplate {
+            uri_template: "solana://wallet/{address}/balances".to_string(),
+            name: "Wallet balances".to_string(),
+            description: "SOL and SPL token balances of any wallet".to_string(),
+            mime_type: JSON_MIME_TYPE.to_string(),
+        },
+        ResourceTemplate {
+            uri_template: "jupiter://quote/{id}".to_string(),
+            name: "Jupiter quote".to_string(),
+            description: "A quote stored by get_quote, addressed by its quoteId".to_string(),
+            mime_type: JSON_MIME_TYPE.to_string(),
+        },
+        ResourceTemplate {
+            uri_template: "solana://tx/{signature}".to_string(),
+            name: "Transaction".to_string(),
+            description: "A confirmed transaction with its status metadata".to_string(),
+            mime_type: JSON_MIME_TYPE.to_string(),
+        },
+    ]
+}
+
+/// Read the resource named by `uri`, already parsed into `resource`
+pub async fn read(ctx: &ToolContext, uri: &str, resource: ResourceUri) -> Result<Vec<ResourceContents>> {
+    let contents = match resource {
+        ResourceUri::WalletBalances(wallet) => wallet_balances(ctx, &wallet).await?,
+        ResourceUri::Quote(id) => serde_json::to_value(ctx.quotes.get(&id)?)?,
+        ResourceUri::Transaction(signature) => transaction(ctx, &signature).await?,
+    };
+
+    Ok(vec![ResourceContents {
+        uri: uri.to_string(),
+        mime_type: JSON_MIME_TYPE.to_string(),
+        text: serde_json::to_string_pretty(&contents)?,
+    }])
+}
+
+async fn wallet_balances(ctx: &ToolContext, wallet: &Pubkey) -> Result<Value> {
+    let lamports = ctx.rpc.get_balance(wallet).await?;
+    let tokens: Vec<Value> = get_token_holdings(&ctx.rpc, wallet)
+        .await?
+        .into_iter()
+        .map(|holding| json!({
+            "mint": holding.mint,
+            "account": holding.account,
+            "amount": holding.amount.to_string(),
+            "decimals": holding.decimals,
+            "uiAmount": TokenAmount::new(holding.amount, holding.decimals).to_plain_string(),
+        }))
+        .collect();
+
+    Ok(json!({
+        "wallet": wallet.to_string(),
+        "sol": {
+            "lamports": lamports.to_string(),
+            "uiAmount": TokenAmount::new(lamports, SOL_DECIMALS).to_plain_string(),
+        },
+        "tokens": tokens,
+    }))
+}
+
+async fn transaction(ctx: &ToolContext, signature: &Signature) -> Result<Value> {
+    let transaction = ctx.rpc.get_transaction_with_config(signature, RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Json),
+        commitment: Some(ctx.config.commitment),
+        max_supported_transaction_version: Some(0),
+    }).await?;
+
+    Ok(serde_json::to_value(transaction)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_resource_uris() {
+        assert_eq!(
+            "solana://wallet/11111111111111111111111111111112/balances".parse::<ResourceUri>().unwrap(),
+            ResourceUri::WalletBalances(parse_pubkey("11111111111111111111111111111112").unwrap())
+        );
+        assert_eq!(
+            "jupiter://quote/abc".parse::<ResourceUri>().unwrap(),
+            ResourceUri::Quote("abc".to_string())
+        );
+        assert!(matches!(
+            "solana://tx/5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW".parse::<ResourceUri>(),
+            Ok(ResourceUri::Transaction(_))
+        ));
+        assert!("solana://wallet/11111111111111111111111111111112".parse::<ResourceUri>().is_err());
+        assert!("jupiter://quote/".parse::<ResourceUri>().is_err());
+        assert!("https://example.com".parse::<ResourceUri>().is_err());
+    }
+}
@@ -1,17 +1,60 @@
 use crate::{Config, JupiterMcpError, Result};
-use crate::mcp::{McpRequest, McpResponse, Tool, ToolCallParams, ToolResponse};
-use crate::tools::{GetQuoteTool, ExecuteSwapTool, GetBalanceTool};
+use crate::journal::{start_of_today, JournalFilter, SwapJournal};
+use crate::jupiter::JupiterClient;
+use crate::mcp::{GetPromptParams, McpRequest, McpResponse, RequestId, ResourceReadParams, Tool, ToolCallParams, ToolResponse};
+use crate::policy::{Policy, PolicyEngine};
+use crate::prompts;
+use crate::quotes::QuoteStore;
+use crate::resources::{self, ResourceUri};
+use crate::tokens::TokenRegistry;
+use crate::session::{InitializeParams, LogLevel, Session, OUTBOUND_CAPACITY};
+use crate::tools::{GetQuoteTool, ExecuteSwapTool, GetBalanceTool, GetPortfolioTool, GetSwapHistoryTool, GetTransactionStatusTool, ToolContext};
+use crate::utils::get_connection;
 use serde_json::{json, Value};
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader as AsyncBufReader};
-use tracing::{error, info, warn};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader as AsyncBufReader};
+use tokio::sync::{mpsc, Semaphore};
+use tokio::task::JoinSet;
+use tracing::{debug, error, info, warn};
 
 pub struct McpServer {
-    config: Config,
+    context: ToolContext,
+    /// Shared by every transport to cap concurrently running requests
+    limiter: Arc<Semaphore>,
 }
 
 impl McpServer {
-    pub fn new(config: Config) -> Self {
-        Self { config }
+    pub fn new(config: Config) -> Result<Self> {
+        let jupiter = JupiterClient::new(&config.jupiter)?;
+        let rpc = Arc::new(get_connection(&config));
+        let limiter = Arc::new(Semaphore::new(config.max_concurrent_requests));
+        let quotes = Arc::new(QuoteStore::new(config.quote_ttl));
+        let policy = match &config.policy_path {
+            Some(path) => Policy::load(path)?,
+            None => Policy::default(),
+        };
+        if policy.confirmation.timeout_secs > config.quote_ttl.as_secs() {
+            warn!(
+                "Confirmation timeout of {}s is longer than the {}s quote lifetime; confirmations will end when the quote expires",
+                policy.confirmation.timeout_secs,
+                config.quote_ttl.as_secs()
+            );
+        }
+        let policy = Arc::new(PolicyEngine::new(policy));
+        let tokens = Arc::new(TokenRegistry::new(config.token_cache_path.clone()));
+        let journal = Arc::new(SwapJournal::open(config.journal_path.clone())?);
+        policy.restore(&journal.query(&JournalFilter {
+            since: Some(start_of_today()),
+            ..JournalFilter::default()
+        })?);
+        Ok(Self {
+            context: ToolContext { config, jupiter, rpc, quotes, policy, tokens, journal },
+            limiter,
+        })
+    }
+    
+    pub fn config(&self) -> &Config {
+        &self.context.config
     }
     
     /// Get list of available tools
@@ -20,6 +63,9 @@ impl McpServer {
             GetQuoteTool::definition(),
             ExecuteSwapTool::definition(),
             GetBalanceTool::definition(),
+            GetPortfolioTool::definition(),
+            GetTransactionStatusTool::definition(),
+            GetSwapHistoryTool::definition(),
         ]
     }
     
@@ -30,83 +76,216 @@ impl McpServer {
     }
     
     /// Handle tools/call request
-    async fn handle_tools_call(&self, params: Value) -> Result<ToolResponse> {
+    async fn handle_tools_call(&self, session: &Session, params: Value) -> Result<ToolResponse> {
         let tool_params: ToolCallParams = serde_json::from_value(params)
             .map_err(|e| JupiterMcpError::InvalidInput(format!("Invalid tool call params: {}", e)))?;
         
         let args = tool_params.arguments.unwrap_or(json!({}));
         
         match tool_params.name.as_str() {
-            "get_quote" => GetQuoteTool::execute(&self.config, args).await,
-            "execute_swap" => ExecuteSwapTool::execute(&self.config, args).await,
-            "get_token_balance" => GetBalanceTool::execute(&self.config, args).await,
+            "get_quote" => GetQuoteTool::execute(&self.context, args).await,
+            "execute_swap" => ExecuteSwapTool::execute(&self.context, session, args).await,
+            "get_token_balance" => GetBalanceTool::execute(&self.context, args).await,
+            "get_portfolio" => GetPortfolioTool::execute(&self.context, args).await,
+            "get_transaction_status" => GetTransactionStatusTool::execute(&self.context, args).await,
+            "get_swap_history" => GetSwapHistoryTool::execute(&self.context, args).await,
             _ => Err(JupiterMcpError::InvalidInput(
                 format!("Unknown tool: {}", tool_params.name)
             )),
         }
     }
     
+    /// Parse resources/read params into the requested URI and what it names
+    fn parse_resources_read(&self, params: Value) -> Result<(String, ResourceUri)> {
+        let params: ResourceReadParams = serde_json::from_value(params)
+            .map_err(|e| JupiterMcpError::InvalidInput(format!("Invalid resource read params: {}", e)))?;
+        
+        let resource = params.uri.parse()?;
+        Ok((params.uri, resource))
+    }
+    
+    /// Handle prompts/get request
+    fn handle_prompts_get(&self, params: Value) -> Result<Value> {
+        let params: GetPromptParams = serde_json::from_value(params)
+            .map_err(|e| JupiterMcpError::InvalidInput(format!("Invalid prompt params: {}", e)))?;
+        
+        prompts::get(&params.name, &params.arguments.unwrap_or_default())
+    }
+    
+    /// Capabilities advertised in `initialize`, limited to what is implemented
+    fn server_capabilities(&self) -> Value {
+        json!({
+            "tools": { "listChanged": false },
+            "resources": { "subscribe": false, "listChanged": false },
+            "prompts": { "listChanged": false },
+            "logging": {}
+        })
+    }
+    
+    /// Handle an incoming message; notifications never produce a response.
+    ///
+    /// Waits for a slot under `max_concurrent_requests` before dispatching.
+    pub async fn handle_message(&self, session: &Session, request: McpRequest) -> Option<McpResponse> {
+        let _permit = self.limiter.acquire().await;
+        match request.id.clone() {
+            Some(id) => Some(self.handle_request(session, id, request).await),
+            None => {
+                self.handle_notification(&request);
+                None
+            }
+        }
+    }
+    
+    /// Handle incoming MCP notification
+    fn handle_notification(&self, notification: &McpRequest) {
+        match notification.method.as_str() {
+            "notifications/initialized" => info!("Client finished initialization"),
+            "notifications/cancelled" => info!("Client cancelled a request: {:?}", notification.params),
+            _ => warn!("Ignoring unknown notification: {}", notification.method),
+        }
+    }
+    
+    /// Handle initialize request
+    fn handle_initialize(&self, session: &Session, params: Value) -> Result<Value> {
+        let params: InitializeParams = serde_json::from_value(params)
+            .map_err(|e| JupiterMcpError::InvalidInput(format!("Invalid initialize params: {}", e)))?;
+        
+        let requested = params.protocol_version.clone();
+        let protocol_version = session.initialize(params);
+        info!("Client requested protocol {}, using {}", requested, protocol_version);
+        
+        Ok(json!({
+            "protocolVersion": protocol_version,
+            "capabilities": self.server_capabilities(),
+            "serverInfo": {
+                "name": "jupiter-ag-mcp",
+                "version": "1.0.0"
+            }
+        }))
+    }
+    
     /// Handle incoming MCP request
-    async fn handle_request(&self, request: McpRequest) -> McpResponse {
+    async fn handle_request(&self, session: &Session, id: RequestId, request: McpRequest) -> McpResponse {
+        if !session.is_initialized() && !matches!(request.method.as_str(), "initialize" | "ping") {
+            warn!("Rejecting {} before initialization", request.method);
+            return McpResponse::error(id, -32002, "Server not initialized".to_string());
+        }
+        
         let result = match request.method.as_str() {
             "tools/list" => {
                 match self.handle_tools_list().await {
                     Ok(result) => Some(result),
                     Err(e) => {
                         error!("Error in tools/list: {}", e);
-                        return McpResponse::error(request.id, -32603, e.to_string());
+                        return McpResponse::error(id, -32603, e.to_string());
                     }
                 }
             }
             "tools/call" => {
                 match request.params {
                     Some(params) => {
-                        match self.handle_tools_call(params).await {
+                        match self.handle_tools_call(session, params).await {
                             Ok(tool_response) => Some(serde_json::to_value(tool_response).unwrap()),
                             Err(e) => {
                                 error!("Error in tools/call: {}", e);
-                                return McpResponse::error(request.id, -32603, e.to_string());
+                                session.log(LogLevel::Error, json!({ "message": e.to_string() }));
+                                return McpResponse::error(id, -32603, e.to_string());
                             }
                         }
                     }
                     None => {
                         warn!("tools/call request missing params");
-                        return McpResponse::error(request.id, -32602, "Missing params".to_string());
+                        return McpResponse::error(id, -32602, "Missing params".to_string());
+                    }
+                }
+            }
+            "resources/list" => Some(json!({ "resources": resources::list(&self.context) })),
+            "resources/templates/list" => Some(json!({ "resourceTemplates": resources::templates() })),
+            "resources/read" => {
+                // A malformed or unknown URI is an invalid param; -32002 is
+                // kept for a valid URI with nothing behind it
+                let (uri, resource) = match self.parse_resources_read(request.params.unwrap_or(Value::Null)) {
+                    Ok(parsed) => parsed,
+                    Err(e) => return McpResponse::error(id, -32602, e.to_string()),
+                };
+                match resources::read(&self.context, &uri, resource).await {
+                    Ok(contents) => Some(json!({ "contents": contents })),
+                    Err(JupiterMcpError::InvalidInput(message)) => {
+                        return McpResponse::error(id, -32002, message);
+                    }
+                    Err(e) => {
+                        error!("Error in resources/read: {}", e);
+                        return McpResponse::error(id, -32603, e.to_string());
                     }
                 }
             }
+            "prompts/list" => Some(json!({ "prompts": prompts::list() })),
+            "prompts/get" => {
+                match self.handle_prompts_get(request.params.unwrap_or(Value::Null)) {
+                    Ok(result) => Some(result),
+                    Err(e) => return McpResponse::error(id, -32602, e.to_string()),
+                }
+            }
             "initialize" => {
                 info!("Client initializing MCP connection");
-                Some(json!({
-                    "protocolVersion": "2024-11-05",
-                    "capabilities": {
-                        "tools": {}
-                    },
-                    "serverInfo": {
-                        "name": "jupiter-ag-mcp",
-                        "version": "1.0.0"
+                if session.is_initialized() {
+                    return McpResponse::error(id, -32600, "Session already initialized".to_string());
+                }
+                match self.handle_initialize(session, request.params.unwrap_or(Value::Null)) {
+                    Ok(result) => Some(result),
+                    Err(e) => return McpResponse::error(id, -32602, e.to_string()),
+                }
+            }
+            "ping" => Some(json!({})),
+            "logging/setLevel" => {
+                let level = request.params
+                    .and_then(|params| params.get("level").cloned())
+                    .and_then(|level| serde_json::from_value::<LogLevel>(level).ok());
+                match level {
+                    Some(level) => {
+                        session.set_log_level(level);
+                        Some(json!({}))
                     }
-                }))
+                    None => return McpResponse::error(id, -32602, "Invalid log level".to_string()),
+                }
             }
             _ => {
                 warn!("Unknown method: {}", request.method);
-                return McpResponse::error(request.id, -32601, "Method not found".to_string());
+                return McpResponse::error(id, -32601, "Method not found".to_string());
             }
         };
         
         match result {
-            Some(result) => McpResponse::success(request.id, result),
-            None => McpResponse::error(request.id, -32603, "Internal error".to_string()),
+            Some(result) => McpResponse::success(id, result),
+            None => McpResponse::error(id, -32603, "Internal error".to_string()),
         }
     }
     
     /// Run the MCP server using stdio transport
-    pub async fn run_stdio(&self) -> Result<()> {
+    pub async fn run_stdio(self: Arc<Self>) -> Result<()> {
         info!("Jupiter AG MCP Server starting on stdio");
         
-        let stdin = tokio::io::stdin();
-        let mut reader = AsyncBufReader::new(stdin);
-        let mut stdout = tokio::io::stdout();
+        self.serve(tokio::io::stdin(), tokio::io::stdout()).await?;
+        
+        info!("Jupiter AG MCP Server shutting down");
+        Ok(())
+    }
+    
+    /// Serve newline-delimited JSON-RPC, handling requests concurrently.
+    ///
+    /// Each request runs on its own task, bounded by `max_concurrent_requests`;
+    /// responses and server-initiated messages are funnelled through a single
+    /// writer task so lines never interleave.
+    pub async fn serve<R, W>(self: Arc<Self>, input: R, output: W) -> Result<()>
+    where
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + Unpin + Send + 'static,
+    {
+        let mut reader = AsyncBufReader::new(input);
+        let (outbound_tx, outbound_rx) = mpsc::channel::<Value>(OUTBOUND_CAPACITY);
+        let writer = tokio::spawn(write_messages(output, outbound_rx));
+        let session = Arc::new(Session::new(outbound_tx));
+        let mut in_flight = JoinSet::new();
         
         let mut line = String::new();
         
@@ -125,36 +304,46 @@ impl McpServer {
                         continue;
                     }
                     
+                    let message: Value = match serde_json::from_str(trimmed) {
+                        Ok(message) => message,
+                        Err(e) => {
+                            error!("Failed to parse request: {} - Input: {}", e, trimmed);
+                            session.send(serde_json::to_value(McpResponse::invalid_message(trimmed))?);
+                            continue;
+                        }
+                    };
+                    
+                    // Responses to our own requests (e.g. elicitation) go to their waiter
+                    if session.complete(&message) {
+                        continue;
+                    }
+                    
                     // Parse the JSON-RPC request
-                    let request: McpRequest = match serde_json::from_str(trimmed) {
+                    let request: McpRequest = match serde_json::from_value(message) {
                         Ok(req) => req,
                         Err(e) => {
                             error!("Failed to parse request: {} - Input: {}", e, trimmed);
-                            let error_response = McpResponse::error(
-                                "unknown".to_string(),
-                                -32700,
-                                "Parse error".to_string(),
-                            );
-                            let response_json = serde_json::to_string(&error_response)?;
-                            stdout.write_all(response_json.as_bytes()).await?;
-                            stdout.write_all(b"\n").await?;
-                            stdout.flush().await?;
+                            session.send(serde_json::to_value(McpResponse::invalid_message(trimmed))?);
                             continue;
                         }
                     };
                     
-                    info!("Handling request: {}", request.method);
-                    
-                    // Handle the request
-                    let response = self.handle_request(request).await;
+                    info!("Handling {}: {}", if request.is_notification() { "notification" } else { "request" }, request.method);
                     
-                    // Send the response
-                    let response_json = serde_json::to_string(&response)?;
-                    stdout.write_all(response_json.as_bytes()).await?;
-                    stdout.write_all(b"\n").await?;
-                    stdout.flush().await?;
+                    // Requests wait for a concurrency slot inside their task so the reader keeps draining stdin
+                    let server = Arc::clone(&self);
+                    let session = Arc::clone(&session);
+                    in_flight.spawn(async move {
+                        if let Some(response) = server.handle_message(&session, request).await {
+                            match serde_json::to_value(response) {
+                                Ok(response) => session.send(response),
+                                Err(e) => error!("Failed to serialize response: {}", e),
+                            }
+                        }
+                    });
                     
-                    info!("Sent response");
+                    // Reap finished tasks so the set does not grow unbounded
+                    while in_flight.try_join_next().is_some() {}
                 }
                 Err(e) => {
                     error!("Error reading from stdin: {}", e);
@@ -163,31 +352,154 @@ impl McpServer {
             }
         }
         
-        info!("Jupiter AG MCP Server shutting down");
-        Ok(())
+        // Let in-flight requests finish before closing the writer
+        while in_flight.join_next().await.is_some() {}
+        drop(session);
+        
+        writer
+            .await
+            .map_err(|e| JupiterMcpError::McpProtocol(format!("Response writer failed: {}", e)))?
+    }
+}
+
+/// Write responses and server-initiated messages one line at a time
+async fn write_messages<W>(mut output: W, mut messages: mpsc::Receiver<Value>) -> Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    while let Some(message) = messages.recv().await {
+        let message_json = serde_json::to_string(&message)?;
+        output.write_all(message_json.as_bytes()).await?;
+        output.write_all(b"\n").await?;
+        output.flush().await?;
+        
+        debug!("Sent message");
     }
+    Ok(())
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
-    use crate::config::SolanaNetwork;
+    use crate::config::{JupiterConfig, SolanaNetwork};
     
-    #[tokio::test]
-    async fn test_tools_list() {
-        let config = Config {
+    pub(crate) fn test_config() -> Config {
+        Config {
             network: SolanaNetwork::Devnet,
             rpc_url: "https://api.devnet.solana.com".to_string(),
             private_key: "test_key".to_string(),
             commitment: solana_sdk::commitment_config::CommitmentConfig::confirmed(),
-        };
-        
-        let server = McpServer::new(config);
+            jupiter: JupiterConfig::default(),
+            max_concurrent_requests: 4,
+            transport: crate::config::Transport::Stdio,
+            http_bind: "127.0.0.1:0".parse().unwrap(),
+            http_allowed_origins: Vec::new(),
+            http_token: None,
+            http_session_ttl: std::time::Duration::from_secs(60),
+            http_max_sessions: 8,
+            quote_ttl: std::time::Duration::from_secs(60),
+            policy_path: None,
+            token_cache_path: None,
+            journal_path: None,
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_tools_list() {
+        let server = McpServer::new(test_config()).unwrap();
         let tools = server.get_tools();
         
-        assert_eq!(tools.len(), 3);
+        assert_eq!(tools.len(), 6);
         assert!(tools.iter().any(|t| t.name == "get_quote"));
         assert!(tools.iter().any(|t| t.name == "execute_swap"));
         assert!(tools.iter().any(|t| t.name == "get_token_balance"));
+        assert!(tools.iter().any(|t| t.name == "get_portfolio"));
+        assert!(tools.iter().any(|t| t.name == "get_transaction_status"));
+        assert!(tools.iter().any(|t| t.name == "get_swap_history"));
     }
-}
\ No newline at end of file
+    
+    #[tokio::test]
+    async fn test_serve_answers_every_request() {
+        let server = Arc::new(McpServer::new(test_config()).unwrap());
+        let input = concat!(
+            r#"{"jsonrpc":"2.0","id":"1","method":"ping"}"#, "\n",
+            "not json\n",
+            r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#, "\n",
+            r#"{"jsonrpc":"2.0","id":2,"method":"initialize","params":{"protocolVersion":"2024-11-05","capabilities":{}}}"#, "\n",
+        );
+        let (output, mut client) = tokio::io::duplex(64 * 1024);
+        
+        server.serve(input.as_bytes(), output).await.unwrap();
+        
+        let mut written = String::new();
+        tokio::io::AsyncReadExt::read_to_string(&mut client, &mut written).await.unwrap();
+        let ids: Vec<Value> = written
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap()["id"].clone())
+            .collect();
+        assert_eq!(ids.len(), 3);
+        assert!(ids.contains(&json!("1")));
+        assert!(ids.contains(&json!(2)));
+        assert!(ids.contains(&Value::Null));
+    }
+    
+    fn test_session() -> (Session, mpsc::Receiver<Value>) {
+        let (tx, rx) = mpsc::channel(OUTBOUND_CAPACITY);
+        (Session::new(tx), rx)
+    }
+    
+    fn request(id: i64, method: &str, params: Value) -> McpRequest {
+        McpRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(RequestId::Number(id.into())),
+            method: method.to_string(),
+            params: Some(params),
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_requests_rejected_before_initialize() {
+        let server = McpServer::new(test_config()).unwrap();
+        let (session, _rx) = test_session();
+        
+        let response = server.handle_request(&session, RequestId::Number(1.into()), request(1, "tools/list", json!({}))).await;
+        assert_eq!(response.error.unwrap().code, -32002);
+    }
+    
+    #[tokio::test]
+    async fn test_initialize_negotiates_version() {
+        let server = McpServer::new(test_config()).unwrap();
+        let (session, _rx) = test_session();
+        
+        let response = server.handle_request(&session, RequestId::Number(1.into()), request(1, "initialize", json!({
+            "protocolVersion": "2025-03-26",
+            "capabilities": { "sampling": {} }
+        }))).await;
+        let result = response.result.unwrap();
+        assert_eq!(result["protocolVersion"], "2025-03-26");
+        assert_eq!(result["capabilities"]["tools"]["listChanged"], false);
+        assert!(result["capabilities"]["resources"].is_object());
+        assert!(session.state().unwrap().client_capabilities.sampling.is_some());
+        
+        let response = server.handle_request(&session, RequestId::Number(2.into()), request(2, "tools/list", json!({}))).await;
+        assert!(response.result.is_some());
+    }
+    
+    #[tokio::test]
+    async fn test_resource_read_error_codes() {
+        let server = McpServer::new(test_config()).unwrap();
+        let (session, _rx) = test_session();
+        server.handle_request(&session, RequestId::Number(1.into()), request(1, "initialize", json!({
+            "protocolVersion": "2025-06-18",
+            "capabilities": {}
+        }))).await;
+        
+        let read = |id: i64, uri: &str| request(id, "resources/read", json!({ "uri": uri }));
+        let response = server.handle_request(&session, RequestId::Number(2.into()), read(2, "https://example.com")).await;
+        assert_eq!(response.error.unwrap().code, -32602);
+        let response = server.handle_request(&session, RequestId::Number(3.into()), read(3, "solana://tx/not-a-signature")).await;
+        assert_eq!(response.error.unwrap().code, -32602);
+        let response = server.handle_request(&session, RequestId::Number(4.into()), read(4, "jupiter://quote/missing")).await;
+        assert_eq!(response.error.unwrap().code, -32002);
+    }
+}
@@ -0,0 +1,301 @@
+use crate::{JupiterMcpError, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use uuid::Uuid;
+
+/// Messages queued for a client before further ones are dropped
+pub const OUTBOUND_CAPACITY: usize = 256;
+
+/// MCP spec revisions the server can speak, newest first
+pub const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+/// Pick the client's requested version when supported, otherwise our latest
+pub fn negotiate_protocol_version(requested: &str) -> &'static str {
+    SUPPORTED_PROTOCOL_VERSIONS
+        .iter()
+        .find(|version| **version == requested)
+        .copied()
+        .unwrap_or(SUPPORTED_PROTOCOL_VERSIONS[0])
+}
+
+/// Capabilities a client declares in `initialize`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClientCapabilities {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sampling: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub roots: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elicitation: Option<Value>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InitializeParams {
+    #[serde(rename = "protocolVersion")]
+    pub protocol_version: String,
+    #[serde(default)]
+    pub capabilities: ClientCapabilities,
+    #[serde(rename = "clientInfo")]
+    pub client_info: Option<Value>,
+}
+
+/// Syslog severities used by `logging/setLevel` and `notifications/message`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Notice,
+    Warning,
+    Error,
+    Critical,
+    Alert,
+    Emergency,
+}
+
+/// State negotiated during `initialize`
+#[derive(Debug, Clone)]
+pub struct SessionState {
+    pub protocol_version: String,
+    pub client_capabilities: ClientCapabilities,
+    pub client_info: Option<Value>,
+}
+
+/// One client connection: its negotiated state plus a channel for
+/// server-initiated messages
+#[derive(Debug)]
+pub struct Session {
+    state: RwLock<Option<SessionState>>,
+    log_level: RwLock<Option<LogLevel>>,
+    outbound: mpsc::Sender<Value>,
+    /// Whether anything is reading `outbound` right now
+    attached: AtomicBool,
+    /// Server-initiated requests awaiting the client's response, by id
+    pending: Mutex<HashMap<String, oneshot::Sender<Value>>>,
+}
+
+impl Session {
+    pub fn new(outbound: mpsc::Sender<Value>) -> Self {
+        Self {
+            state: RwLock::new(None),
+            log_level: RwLock::new(None),
+            outbound,
+            attached: AtomicBool::new(true),
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Whether server-initiated messages currently reach the client. Always
+    /// true on stdio; over HTTP only while a GET stream is open.
+    pub fn is_attached(&self) -> bool {
+        self.attached.load(Ordering::SeqCst)
+    }
+
+    pub fn set_attached(&self, attached: bool) {
+        self.attached.store(attached, Ordering::SeqCst);
+    }
+
+    /// Record the client's initialize parameters and return the negotiated version
+    pub fn initialize(&self, params: InitializeParams) -> &'static str {
+        let protocol_version = negotiate_protocol_version(&params.protocol_version);
+        *self.state.write().unwrap() = Some(SessionState {
+            protocol_version: protocol_version.to_string(),
+            client_capabilities: params.capabilities,
+            client_info: params.client_info,
+        });
+        protocol_version
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.state.read().unwrap().is_some()
+    }
+
+    pub fn state(&self) -> Option<SessionState> {
+        self.state.read().unwrap().clone()
+    }
+
+    /// Whether the client declared the `elicitation` capability
+    pub fn supports_elicitation(&self) -> bool {
+        matches!(&*self.state.read().unwrap(), Some(state) if state.client_capabilities.elicitation.is_some())
+    }
+
+    pub fn set_log_level(&self, level: LogLevel) {
+        *self.log_level.write().unwrap() = Some(level);
+    }
+
+    /// Send a server-initiated notification to the client
+    pub fn notify(&self, method: &str, params: Value) {
+        self.send(json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+        }));
+    }
+
+    /// Forward a log message once the client has opted in via `logging/setLevel`
+    pub fn log(&self, level: LogLevel, data: Value) {
+        let enabled = matches!(*self.log_level.read().unwrap(), Some(min) if level >= min);
+        if enabled {
+            self.notify("notifications/message", json!({
+                "level": level,
+                "logger": "jupiter-ag-mcp",
+                "data": data,
+            }));
+        }
+    }
+
+    /// Queue a response on the same channel as server-initiated messages.
+    /// A client that stops reading loses messages once the queue is full.
+    pub fn send(&self, message: Value) {
+        if let Err(mpsc::error::TrySendError::Full(_)) = self.outbound.try_send(message) {
+            tracing::warn!("Outbound queue full, dropping a message for the client");
+        }
+    }
+
+    /// Send a request to the client and wait up to `timeout` for its result
+    pub async fn request(&self, method: &str, params: Value, timeout: Duration) -> Result<Value> {
+        let id = format!("server-{}", Uuid::new_v4());
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id.clone(), tx);
+
+        self.send(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        }));
+
+        let response = tokio::time::timeout(timeout, rx).await;
+        self.pending.lock().unwrap().remove(&id);
+
+        let response = match response {
+            Ok(Ok(response)) => response,
+            Ok(Err(_)) => return Err(JupiterMcpError::McpProtocol(format!("{} was abandoned", method))),
+            Err(_) => return Err(JupiterMcpError::McpProtocol(format!(
+                "Client did not answer {} within {}s", method, timeout.as_secs()
+            ))),
+        };
+
+        match response.get("error") {
+            Some(error) => Err(JupiterMcpError::McpProtocol(format!("Client rejected {}: {}", method, error))),
+            None => Ok(response.get("result").cloned().unwrap_or(Value::Null)),
+        }
+    }
+
+    /// Route a client response to the pending server request it answers.
+    /// Returns false when the message is not such a response.
+    pub fn complete(&self, message: &Value) -> bool {
+        let is_response = message.get("method").is_none()
+            && (message.get("result").is_some() || message.get("error").is_some());
+        let Some(id) = message.get("id").and_then(Value::as_str).filter(|_| is_response) else {
+            return false;
+        };
+
+        match self.pending.lock().unwrap().remove(id) {
+            Some(waiter) => {
+                let _ = waiter.send(message.clone());
+            }
+            None => tracing::warn!("Dropping response to unknown request {}", id),
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate_protocol_version() {
+        assert_eq!(negotiate_protocol_version("2024-11-05"), "2024-11-05");
+        assert_eq!(negotiate_protocol_version("2025-03-26"), "2025-03-26");
+        assert_eq!(negotiate_protocol_version("1999-01-01"), SUPPORTED_PROTOCOL_VERSIONS[0]);
+    }
+
+    #[test]
+    fn test_initialize_stores_client_capabilities() {
+        let (tx, _rx) = mpsc::channel(OUTBOUND_CAPACITY);
+        let session = Session::new(tx);
+        assert!(!session.is_initialized());
+
+        let params: InitializeParams = serde_json::from_value(json!({
+            "protocolVersion": "2025-06-18",
+            "capabilities": { "elicitation": {}, "roots": { "listChanged": true } },
+            "clientInfo": { "name": "test", "version": "1.0" }
+        })).unwrap();
+        session.initialize(params);
+
+        let state = session.state().unwrap();
+        assert_eq!(state.protocol_version, "2025-06-18");
+        assert!(state.client_capabilities.elicitation.is_some());
+        assert!(state.client_capabilities.sampling.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_request_is_completed_by_client_response() {
+        let (tx, mut rx) = mpsc::channel(OUTBOUND_CAPACITY);
+        let session = std::sync::Arc::new(Session::new(tx));
+
+        let waiter = {
+            let session = std::sync::Arc::clone(&session);
+            tokio::spawn(async move {
+                session.request("elicitation/create", json!({ "message": "ok?" }), Duration::from_secs(5)).await
+            })
+        };
+
+        let request = rx.recv().await.unwrap();
+        assert_eq!(request["method"], "elicitation/create");
+        assert!(!session.complete(&json!({ "jsonrpc": "2.0", "id": 1, "method": "ping" })));
+        assert!(session.complete(&json!({ "jsonrpc": "2.0", "id": request["id"], "result": { "action": "accept" } })));
+
+        let result = waiter.await.unwrap().unwrap();
+        assert_eq!(result["action"], "accept");
+    }
+
+    #[tokio::test]
+    async fn test_request_times_out() {
+        let (tx, _rx) = mpsc::channel(OUTBOUND_CAPACITY);
+        let session = Session::new(tx);
+
+        let err = session.request("elicitation/create", json!({}), Duration::from_millis(10)).await.unwrap_err();
+        assert!(err.to_string().contains("did not answer"));
+        assert!(session.pending.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_full_queue_drops_messages() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let session = Session::new(tx);
+
+        session.notify("notifications/first", json!({}));
+        session.notify("notifications/second", json!({}));
+
+        assert_eq!(rx.try_recv().unwrap()["method"], "notifications/first");
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_log_respects_level() {
+        let (tx, mut rx) = mpsc::channel(OUTBOUND_CAPACITY);
+        let session = Session::new(tx);
+
+        session.log(LogLevel::Error, json!("dropped before setLevel"));
+        assert!(rx.try_recv().is_err());
+
+        session.set_log_level(LogLevel::Warning);
+        session.log(LogLevel::Info, json!("below threshold"));
+        session.log(LogLevel::Error, json!("delivered"));
+
+        let message = rx.try_recv().unwrap();
+        assert_eq!(message["method"], "notifications/message");
+        assert_eq!(message["params"]["level"], "error");
+        assert!(rx.try_recv().is_err());
+    }
+}
@@ -0,0 +1,429 @@
+use crate::tools::get_quote::QuoteResponse;
+use crate::utils::{TokenAmount, SOL_DECIMALS};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use solana_transaction_status::{
+    option_serializer::OptionSerializer, EncodedConfirmedTransactionWithStatusMeta,
+    EncodedTransaction, UiMessage, UiTransactionTokenBalance,
+};
+use std::collections::{BTreeMap, BTreeSet};
+use tracing::warn;
+
+/// Decimal places realized and quoted prices are computed to
+const PRICE_DECIMALS: u8 = 9;
+
+/// Change in the wallet's balance of one mint, summed over its token accounts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TokenDelta {
+    pub mint: String,
+    pub decimals: u8,
+    pub pre: u64,
+    pub post: u64,
+    pub change: i128,
+}
+
+/// Balance changes of one wallet in a landed transaction
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct WalletDeltas {
+    /// Lamports gained or lost, not counting the transaction fee
+    #[serde(rename = "solChange")]
+    pub sol_change: i128,
+    /// Fee charged to the wallet, 0 when another account paid it
+    #[serde(rename = "feePaid")]
+    pub fee_paid: u64,
+    pub tokens: Vec<TokenDelta>,
+}
+
+impl WalletDeltas {
+    /// Read the wallet's changes from `pre/postBalances` and
+    /// `pre/postTokenBalances`. Returns `None` when the node sent no metadata.
+    /// A mint with a token balance that is not a valid integer is left out.
+    pub fn from_transaction(
+        transaction: &EncodedConfirmedTransactionWithStatusMeta,
+        wallet: &Pubkey,
+    ) -> Option<Self> {
+        let meta = transaction.transaction.meta.as_ref()?;
+        let wallet = wallet.to_string();
+
+        // Signers are always static keys, so their index matches the balance lists
+        let index = account_keys(&transaction.transaction.transaction)
+            .iter()
+            .position(|key| *key == wallet);
+        let (sol_change, fee_paid) = match index {
+            Some(index) => {
+                let pre = *meta.pre_balances.get(index)? as i128;
+                let post = *meta.post_balances.get(index)? as i128;
+                let fee = if index == 0 { meta.fee } else { 0 };
+                (post - pre + fee as i128, fee)
+            }
+            None => (0, 0),
+        };
+
+        let mut balances: BTreeMap<String, (u8, u64, u64)> = BTreeMap::new();
+        // Mints whose balances could not be read or summed; a delta from the
+        // rest of their accounts would be wrong
+        let mut unreadable = BTreeSet::new();
+        let pre = owned_by(&meta.pre_token_balances, &wallet).map(|balance| (false, balance));
+        let post = owned_by(&meta.post_token_balances, &wallet).map(|balance| (true, balance));
+        for (is_post, balance) in pre.chain(post) {
+            let entry = balances.entry(balance.mint.clone()).or_insert((balance.ui_token_amount.decimals, 0, 0));
+            let total = if is_post { &mut entry.2 } else { &mut entry.1 };
+            match token_amount(balance).and_then(|amount| total.checked_add(amount)) {
+                Some(sum) => *total = sum,
+                None => {
+                    warn!("Skipping unreadable {} balance of {}", balance.mint, balance.ui_token_amount.amount);
+                    unreadable.insert(balance.mint.clone());
+                }
+            }
+        }
+
+        let tokens = balances
+            .into_iter()
+            .filter(|(mint, (_, pre, post))| pre != post && !unreadable.contains(mint))
+            .map(|(mint, (decimals, pre, post))| TokenDelta {
+                mint,
+                decimals,
+                pre,
+                post,
+                change: post as i128 - pre as i128,
+            })
+            .collect();
+
+        Some(Self { sol_change, fee_paid, tokens })
+    }
+
+    /// Net change of a mint. Wrapped SOL also counts the wallet's lamports,
+    /// since swaps wrap and unwrap it in a temporary account.
+    pub fn change(&self, mint: &str) -> i128 {
+        let tokens: i128 = self.tokens.iter().filter(|delta| delta.mint == mint).map(|delta| delta.change).sum();
+        if is_native(mint) {
+            tokens + self.sol_change
+        } else {
+            tokens
+        }
+    }
+
+    pub fn decimals(&self, mint: &str) -> Option<u8> {
+        self.tokens
+            .iter()
+            .find(|delta| delta.mint == mint)
+            .map(|delta| delta.decimals)
+            .or_else(|| is_native(mint).then_some(SOL_DECIMALS))
+    }
+}
+
+/// What a swap actually spent and received, compared with its quote.
+///
+/// For SOL inputs the amount spent also includes rent for any token account
+/// the swap had to open.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RealizedSwap {
+    #[serde(rename = "inputMint")]
+    pub input_mint: String,
+    #[serde(rename = "outputMint")]
+    pub output_mint: String,
+    #[serde(rename = "inputSpent")]
+    pub input_spent: u64,
+    #[serde(rename = "outputReceived")]
+    pub output_received: u64,
+    #[serde(rename = "inputDecimals")]
+    pub input_decimals: Option<u8>,
+    #[serde(rename = "outputDecimals")]
+    pub output_decimals: Option<u8>,
+    #[serde(rename = "quotedInput")]
+    pub quoted_input: u64,
+    #[serde(rename = "quotedOutput")]
+    pub quoted_output: u64,
+    /// Minimum output for ExactIn swaps, maximum input for ExactOut swaps
+    #[serde(rename = "otherAmountThreshold")]
+    pub other_amount_threshold: u64,
+    #[serde(rename = "exactOut")]
+    pub exact_out: bool,
+    /// Whole output tokens received per whole input token, as a decimal
+    /// string rounded to 9 places
+    #[serde(rename = "realizedPrice")]
+    pub realized_price: Option<String>,
+    #[serde(rename = "quotedPrice")]
+    pub quoted_price: Option<String>,
+    /// How much worse than quoted the swap filled, negative when it did better
+    #[serde(rename = "slippageBps")]
+    pub slippage_bps: f64,
+    #[serde(rename = "withinThreshold")]
+    pub within_threshold: bool,
+}
+
+impl RealizedSwap {
+    /// Compare the wallet's balance changes with the quote. Returns `None` if
+    /// the quote amounts are not valid integers.
+    pub fn new(quote: &QuoteResponse, deltas: &WalletDeltas) -> Option<Self> {
+        let quoted_input: u64 = quote.in_amount.parse().ok()?;
+        let quoted_output: u64 = quote.out_amount.parse().ok()?;
+        let other_amount_threshold: u64 = quote.other_amount_threshold.parse().ok()?;
+        let exact_out = quote.swap_mode == "ExactOut";
+
+        let input_spent = clamp(-deltas.change(&quote.input_mint));
+        let output_received = clamp(deltas.change(&quote.output_mint));
+        let input_decimals = deltas.decimals(&quote.input_mint);
+        let output_decimals = deltas.decimals(&quote.output_mint);
+
+        let price = |input: u64, output: u64| price(input, input_decimals?, output, output_decimals?);
+
+        let (slippage_bps, within_threshold) = if exact_out {
+            (bps(input_spent as f64 - quoted_input as f64, quoted_input), input_spent <= other_amount_threshold)
+        } else {
+            (bps(quoted_output as f64 - output_received as f64, quoted_output), output_received >= other_amount_threshold)
+        };
+
+        Some(Self {
+            input_mint: quote.input_mint.clone(),
+            output_mint: quote.output_mint.clone(),
+            input_spent,
+            output_received,
+            input_decimals,
+            output_decimals,
+            quoted_input,
+            quoted_output,
+            other_amount_threshold,
+            exact_out,
+            realized_price: price(input_spent, output_received),
+            quoted_price: price(quoted_input, quoted_output),
+            slippage_bps,
+            within_threshold,
+        })
+    }
+
+    /// Human readable summary, with the mints shown as the given labels
+    pub fn to_text(&self, input_label: &str, output_label: &str) -> String {
+        let input = |amount| format_amount(amount, self.input_decimals, input_label);
+        let output = |amount| format_amount(amount, self.output_decimals, output_label);
+
+        let (input_bound, output_bound) = if self.exact_out {
+            (format!(", maximum {}", input(self.other_amount_threshold)), String::new())
+        } else {
+            (String::new(), format!(", minimum {}", output(self.other_amount_threshold)))
+        };
+
+        let mut text = format!(
+            "Input spent: {} (quoted {}{})\n\
+            Output received: {} (quoted {}{})",
+            input(self.input_spent),
+            input(self.quoted_input),
+            input_bound,
+            output(self.output_received),
+            output(self.quoted_output),
+            output_bound,
+        );
+        if let (Some(realized), Some(quoted)) = (&self.realized_price, &self.quoted_price) {
+            text.push_str(&format!(
+                "\nRealized price: {} {} per {} (quoted {})",
+                realized, output_label, input_label, quoted
+            ));
+        }
+        text.push_str(&format!("\nSlippage vs quote: {:.1} bps", self.slippage_bps));
+        if !self.within_threshold {
+            text.push_str("\n⚠️ The fill is outside the quote's slippage threshold");
+        }
+        text
+    }
+}
+
+/// Static account keys of an encoded transaction, whatever its encoding
+fn account_keys(transaction: &EncodedTransaction) -> Vec<String> {
+    match transaction {
+        EncodedTransaction::Json(transaction) => match &transaction.message {
+            UiMessage::Raw(message) => message.account_keys.clone(),
+            UiMessage::Parsed(message) => message.account_keys.iter().map(|key| key.pubkey.clone()).collect(),
+        },
+        EncodedTransaction::Accounts(list) => list.account_keys.iter().map(|key| key.pubkey.clone()).collect(),
+        encoded => encoded
+            .decode()
+            .map(|transaction| transaction.message.static_account_keys().iter().map(ToString::to_string).collect())
+            .unwrap_or_default(),
+    }
+}
+
+fn owned_by<'a>(
+    balances: &'a OptionSerializer<Vec<UiTransactionTokenBalance>>,
+    owner: &'a str,
+) -> impl Iterator<Item = &'a UiTransactionTokenBalance> {
+    let balances: &[UiTransactionTokenBalance] = match balances {
+        OptionSerializer::Some(balances) => balances,
+        _ => &[],
+    };
+    balances
+        .iter()
+        .filter(move |balance| matches!(&balance.owner, OptionSerializer::Some(key) if key == owner))
+}
+
+fn token_amount(balance: &UiTransactionTokenBalance) -> Option<u64> {
+    balance.ui_token_amount.amount.parse().ok()
+}
+
+fn is_native(mint: &str) -> bool {
+    mint == spl_token::native_mint::id().to_string()
+}
+
+fn clamp(change: i128) -> u64 {
+    change.clamp(0, u64::MAX as i128) as u64
+}
+
+/// Whole output tokens per whole input token, rounded to `PRICE_DECIMALS`
+/// places with integer math. `None` without input or on overflow.
+fn price(input: u64, input_decimals: u8, output: u64, output_decimals: u8) -> Option<String> {
+    let scale = |decimals: u8| 10u128.checked_pow(decimals as u32);
+    let numerator = (output as u128).checked_mul(scale(input_decimals.checked_add(PRICE_DECIMALS)?)?)?;
+    let denominator = (input as u128).checked_mul(scale(output_decimals)?)?;
+    if denominator == 0 {
+        return None;
+    }
+    let raw = numerator.checked_add(denominator / 2)? / denominator;
+    Some(TokenAmount::new(raw, PRICE_DECIMALS).to_plain_string())
+}
+
+fn bps(difference: f64, quoted: u64) -> f64 {
+    if quoted == 0 {
+        0.0
+    } else {
+        difference / quoted as f64 * 10_000.0
+    }
+}
+
+fn format_amount(amount: u64, decimals: Option<u8>, label: &str) -> String {
+    match decimals {
+        Some(decimals) => format!("{} {}", TokenAmount::new(amount, decimals), label),
+        None => format!("{} base units of {}", amount, label),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::get_quote::test_quotes::{sample_quote, SOL, USDC};
+    use serde_json::{json, Value};
+
+    const WALLET: &str = "C6eBmAXKg6JhJWkajGa5YRGUfG4YKXwbxF5Ufv7PtExZ";
+
+    fn token_balance(index: u8, mint: &str, amount: u64, decimals: u8) -> Value {
+        json!({
+            "accountIndex": index,
+            "mint": mint,
+            "owner": WALLET,
+            "programId": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+            "uiTokenAmount": {
+                "uiAmount": null,
+                "decimals": decimals,
+                "amount": amount.to_string(),
+                "uiAmountString": ""
+            }
+        })
+    }
+
+    /// SOL -> USDC swap: 1 SOL unwrapped from lamports, 99.5 USDC received
+    fn swap_transaction() -> EncodedConfirmedTransactionWithStatusMeta {
+        serde_json::from_value(json!({
+            "slot": 7,
+            "blockTime": null,
+            "transaction": {
+                "signatures": ["1111111111111111111111111111111111111111111111111111111111111111"],
+                "message": {
+                    "header": {
+                        "numRequiredSignatures": 1,
+                        "numReadonlySignedAccounts": 0,
+                        "numReadonlyUnsignedAccounts": 0
+                    },
+                    "accountKeys": [WALLET, "2Gd5eoR5J4BV89uXbtunpbNhjmw3wa1NbRHxTHzDzZLX"],
+                    "recentBlockhash": "D37n3BSG71oUWcWjbZ37jZP7UfsxG2QMKeuALJ1PYvM6",
+                    "instructions": []
+                }
+            },
+            "meta": {
+                "err": null,
+                "status": {"Ok": null},
+                "fee": 5000,
+                "preBalances": [3_000_000_000u64, 0],
+                "postBalances": [1_999_995_000u64, 0],
+                "preTokenBalances": [token_balance(1, USDC, 10_000_000, 6)],
+                "postTokenBalances": [token_balance(1, USDC, 109_500_000, 6)],
+                "innerInstructions": [],
+                "logMessages": [],
+                "rewards": []
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_wallet_deltas() {
+        let wallet = WALLET.parse().unwrap();
+        let deltas = WalletDeltas::from_transaction(&swap_transaction(), &wallet).unwrap();
+
+        assert_eq!(deltas.fee_paid, 5000);
+        assert_eq!(deltas.sol_change, -1_000_000_000);
+        assert_eq!(deltas.change(SOL), -1_000_000_000);
+        assert_eq!(deltas.change(USDC), 99_500_000);
+        assert_eq!(deltas.decimals(USDC), Some(6));
+
+        let stranger = Pubkey::new_unique();
+        let deltas = WalletDeltas::from_transaction(&swap_transaction(), &stranger).unwrap();
+        assert_eq!(deltas, WalletDeltas::default());
+    }
+
+    #[test]
+    fn test_realized_swap_against_quote() {
+        let wallet = WALLET.parse().unwrap();
+        let deltas = WalletDeltas::from_transaction(&swap_transaction(), &wallet).unwrap();
+        let realized = RealizedSwap::new(&sample_quote(), &deltas).unwrap();
+
+        assert_eq!(realized.input_spent, 1_000_000_000);
+        assert_eq!(realized.output_received, 99_500_000);
+        assert_eq!(realized.realized_price.as_deref(), Some("99.5"));
+        assert_eq!(realized.quoted_price.as_deref(), Some("100"));
+        assert!((realized.slippage_bps - 50.0).abs() < 1e-9);
+        assert!(realized.within_threshold);
+
+        let text = realized.to_text("SOL", "USDC");
+        assert!(text.contains("Output received: 99.5 USDC (quoted 100 USDC, minimum 99 USDC)"));
+        assert!(text.contains("Realized price: 99.5 USDC per SOL (quoted 100)"));
+        assert!(text.contains("Slippage vs quote: 50.0 bps"));
+    }
+
+    #[test]
+    fn test_price_precision() {
+        assert_eq!(price(3_000_000_000, 9, 1_000_000, 6).as_deref(), Some("0.333333333"));
+        assert_eq!(price(3_000_000, 6, 2_000_000_000, 9).as_deref(), Some("0.666666667"));
+        assert_eq!(price(0, 9, 1_000_000, 6), None);
+        assert_eq!(price(u64::MAX, 9, u64::MAX, 200), None);
+    }
+
+    #[test]
+    fn test_unreadable_token_amount() {
+        let mut transaction = serde_json::to_value(swap_transaction()).unwrap();
+        transaction["meta"]["postTokenBalances"][0]["uiTokenAmount"]["amount"] = json!("lots");
+        let transaction = serde_json::from_value(transaction).unwrap();
+
+        let deltas = WalletDeltas::from_transaction(&transaction, &WALLET.parse().unwrap()).unwrap();
+        assert!(deltas.tokens.is_empty());
+        assert_eq!(deltas.sol_change, -1_000_000_000);
+        assert_eq!(deltas.fee_paid, 5000);
+
+        // Balances that overflow when summed are skipped the same way
+        let mut transaction = serde_json::to_value(swap_transaction()).unwrap();
+        transaction["meta"]["postTokenBalances"] =
+            json!([token_balance(1, USDC, u64::MAX, 6), token_balance(2, USDC, 1, 6)]);
+        let transaction = serde_json::from_value(transaction).unwrap();
+        let deltas = WalletDeltas::from_transaction(&transaction, &WALLET.parse().unwrap()).unwrap();
+        assert!(deltas.tokens.is_empty());
+    }
+
+    #[test]
+    fn test_realized_swap_outside_threshold() {
+        let wallet = WALLET.parse().unwrap();
+        let deltas = WalletDeltas::from_transaction(&swap_transaction(), &wallet).unwrap();
+        let mut quote = sample_quote();
+        quote.other_amount_threshold = "99900000".to_string();
+
+        let realized = RealizedSwap::new(&quote, &deltas).unwrap();
+        assert!(!realized.within_threshold);
+        assert!(realized.to_text("SOL", "USDC").contains("outside"));
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{JupiterMcpError, Result};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    transaction::VersionedTransaction,
+};
+
+/// Sign a transaction built by Jupiter with the configured wallet.
+///
+/// The wallet must be the fee payer. Any other required signer must already
+/// have signed, since the server only holds the wallet key.
+pub fn sign_transaction(
+    mut transaction: VersionedTransaction,
+    wallet: &Keypair,
+) -> Result<VersionedTransaction> {
+    let wallet_pubkey = wallet.pubkey();
+    let num_required = transaction.message.header().num_required_signatures as usize;
+    let signer_keys: Vec<Pubkey> = transaction
+        .message
+        .static_account_keys()
+        .iter()
+        .take(num_required)
+        .copied()
+        .collect();
+
+    let fee_payer = signer_keys.first().ok_or_else(|| {
+        JupiterMcpError::Signing("Transaction does not declare a fee payer".to_string())
+    })?;
+
+    if *fee_payer != wallet_pubkey {
+        return Err(JupiterMcpError::Signing(format!(
+            "Fee payer {} does not match the configured wallet {}",
+            fee_payer, wallet_pubkey
+        )));
+    }
+
+    // Unsigned transactions may come with an empty or zero-filled signature list
+    transaction.signatures.resize(num_required, Signature::default());
+
+    let missing: Vec<String> = signer_keys
+        .iter()
+        .zip(transaction.signatures.iter())
+        .filter(|(key, signature)| **key != wallet_pubkey && **signature == Signature::default())
+        .map(|(key, _)| key.to_string())
+        .collect();
+
+    if !missing.is_empty() {
+        return Err(JupiterMcpError::Signing(format!(
+            "Transaction requires signatures the server does not hold: {}",
+            missing.join(", ")
+        )));
+    }
+
+    let signature = wallet.sign_message(&transaction.message.serialize());
+    for (key, slot) in signer_keys.iter().zip(transaction.signatures.iter_mut()) {
+        if *key == wallet_pubkey {
+            *slot = signature;
+        }
+    }
+
+    if !transaction.verify_with_results().into_iter().all(|valid| valid) {
+        return Err(JupiterMcpError::Signing(
+            "Signature verification failed after signing".to_string(),
+        ));
+    }
+
+    Ok(transaction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{
+        hash::Hash,
+        instruction::{AccountMeta, Instruction},
+        message::{v0, VersionedMessage},
+        system_instruction,
+    };
+
+    fn unsigned_transaction(payer: &Pubkey, instructions: &[Instruction]) -> VersionedTransaction {
+        let message = v0::Message::try_compile(payer, instructions, &[], Hash::new_unique()).unwrap();
+        let num_required = message.header.num_required_signatures as usize;
+        VersionedTransaction {
+            signatures: vec![Signature::default(); num_required],
+            message: VersionedMessage::V0(message),
+        }
+    }
+
+    #[test]
+    fn test_sign_transaction_as_fee_payer() {
+        let wallet = Keypair::new();
+        let instruction = system_instruction::transfer(&wallet.pubkey(), &Pubkey::new_unique(), 1);
+        let transaction = unsigned_transaction(&wallet.pubkey(), &[instruction]);
+
+        let signed = sign_transaction(transaction, &wallet).unwrap();
+
+        assert_eq!(signed.signatures.len(), 1);
+        assert!(signed.signatures[0].verify(wallet.pubkey().as_ref(), &signed.message.serialize()));
+        assert!(signed.verify_with_results().into_iter().all(|valid| valid));
+    }
+
+    #[test]
+    fn test_sign_transaction_fills_empty_signature_list() {
+        let wallet = Keypair::new();
+        let instruction = system_instruction::transfer(&wallet.pubkey(), &Pubkey::new_unique(), 1);
+        let mut transaction = unsigned_transaction(&wallet.pubkey(), &[instruction]);
+        transaction.signatures.clear();
+
+        let signed = sign_transaction(transaction, &wallet).unwrap();
+        assert!(signed.verify_with_results().into_iter().all(|valid| valid));
+    }
+
+    #[test]
+    fn test_sign_transaction_rejects_foreign_fee_payer() {
+        let wallet = Keypair::new();
+        let other = Keypair::new();
+        let instruction = system_instruction::transfer(&other.pubkey(), &Pubkey::new_unique(), 1);
+        let transaction = unsigned_transaction(&other.pubkey(), &[instruction]);
+
+        let err = sign_transaction(transaction, &wallet).unwrap_err();
+        assert!(err.to_string().contains("does not match the configured wallet"));
+    }
+
+    #[test]
+    fn test_sign_transaction_rejects_missing_signer() {
+        let wallet = Keypair::new();
+        let cosigner = Keypair::new();
+        let instruction = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[],
+            vec![
+                AccountMeta::new(wallet.pubkey(), true),
+                AccountMeta::new_readonly(cosigner.pubkey(), true),
+            ],
+        );
+        let transaction = unsigned_transaction(&wallet.pubkey(), &[instruction]);
+
+        let err = sign_transaction(transaction, &wallet).unwrap_err();
+        assert!(err.to_string().contains(&cosigner.pubkey().to_string()));
+    }
+
+    #[test]
+    fn test_sign_transaction_keeps_existing_cosigner_signature() {
+        let wallet = Keypair::new();
+        let cosigner = Keypair::new();
+        let instruction = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[],
+            vec![
+                AccountMeta::new(wallet.pubkey(), true),
+                AccountMeta::new_readonly(cosigner.pubkey(), true),
+            ],
+        );
+        let mut transaction = unsigned_transaction(&wallet.pubkey(), &[instruction]);
+        transaction.signatures[1] = cosigner.sign_message(&transaction.message.serialize());
+
+        let signed = sign_transaction(transaction, &wallet).unwrap();
+        assert!(signed.verify_with_results().into_iter().all(|valid| valid));
+    }
+}
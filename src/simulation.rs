@@ -0,0 +1,191 @@
+use crate::mint::get_mint_info;
+use crate::utils::parse_pubkey;
+use crate::Result;
+use serde::Serialize;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig};
+use solana_sdk::{
+    program_pack::Pack,
+    pubkey::Pubkey,
+    transaction::VersionedTransaction,
+};
+use spl_token::state::Account as TokenAccount;
+
+/// Balance of one account before and after the simulated transaction
+#[derive(Debug, Clone, Serialize)]
+pub struct BalanceChange {
+    /// "SOL" for the wallet's lamports, otherwise the token mint
+    pub asset: String,
+    pub account: String,
+    pub pre: u64,
+    pub post: u64,
+}
+
+/// Outcome of `simulateTransaction` for a signed swap
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulationReport {
+    pub error: Option<String>,
+    pub units_consumed: Option<u64>,
+    pub logs: Vec<String>,
+    pub balance_changes: Vec<BalanceChange>,
+}
+
+impl SimulationReport {
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::from("🧪 Simulation only - nothing was broadcast\n\n");
+
+        match &self.error {
+            None => text.push_str("Result: the swap would succeed\n"),
+            Some(error) => text.push_str(&format!("Result: the swap would FAIL: {}\n", error)),
+        }
+        if let Some(units) = self.units_consumed {
+            text.push_str(&format!("Compute units consumed: {}\n", units));
+        }
+
+        if !self.balance_changes.is_empty() {
+            text.push_str("\nBalance changes (base units):\n");
+            for change in &self.balance_changes {
+                let delta = change.post as i128 - change.pre as i128;
+                text.push_str(&format!(
+                    "  {} ({}): {} → {} ({:+})\n",
+                    change.asset, change.account, change.pre, change.post, delta
+                ));
+            }
+        }
+
+        if !self.logs.is_empty() {
+            text.push_str("\nLogs:\n");
+            for line in &self.logs {
+                text.push_str(&format!("  {}\n", line));
+            }
+        }
+
+        text
+    }
+}
+
+/// Simulate a signed swap and report how it would change the wallet's
+/// SOL and input/output token balances
+pub async fn simulate_swap(
+    rpc: &RpcClient,
+    wallet: &Pubkey,
+    input_mint: &str,
+    output_mint: &str,
+    transaction: &VersionedTransaction,
+) -> Result<SimulationReport> {
+    let mut watched = vec![("SOL".to_string(), *wallet)];
+    for mint in [input_mint, output_mint] {
+        // The associated account address depends on the mint's token program
+        let token_account = get_mint_info(rpc, &parse_pubkey(mint)?).await?.associated_token_address(wallet);
+        watched.push((mint.to_string(), token_account));
+    }
+    let addresses: Vec<Pubkey> = watched.iter().map(|(_, address)| *address).collect();
+
+    let pre_accounts = rpc.get_multiple_accounts(&addresses).await?;
+
+    let result = rpc.simulate_transaction_with_config(transaction, RpcSimulateTransactionConfig {
+        sig_verify: true,
+        commitment: Some(rpc.commitment()),
+        accounts: Some(RpcSimulateTransactionAccountsConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            addresses: addresses.iter().map(Pubkey::to_string).collect(),
+        }),
+        ..Default::default()
+    }).await?.value;
+
+    let post_accounts = result.accounts.unwrap_or_default();
+
+    let balance_changes = watched
+        .iter()
+        .enumerate()
+        .filter_map(|(i, (asset, address))| {
+            let is_sol = i == 0;
+            let pre = pre_accounts.get(i).cloned().flatten().map(|account| {
+                if is_sol { Some(account.lamports) } else { token_amount(&account.data) }
+            });
+            let post = post_accounts.get(i).cloned().flatten().map(|account| {
+                if is_sol { Some(account.lamports) } else { account.data.decode().as_deref().and_then(token_amount) }
+            });
+            // Skip token accounts that exist neither before nor after
+            if pre.is_none() && post.is_none() {
+                return None;
+            }
+            Some(BalanceChange {
+                asset: asset.clone(),
+                account: address.to_string(),
+                pre: pre.flatten().unwrap_or(0),
+                post: post.flatten().unwrap_or(0),
+            })
+        })
+        .collect();
+
+    Ok(SimulationReport {
+        error: result.err.map(|e| e.to_string()),
+        units_consumed: result.units_consumed,
+        logs: result.logs.unwrap_or_default(),
+        balance_changes,
+    })
+}
+
+/// Amount held by a token account, ignoring any trailing extension data
+fn token_amount(data: &[u8]) -> Option<u64> {
+    let base = data.get(..TokenAccount::LEN)?;
+    TokenAccount::unpack_from_slice(base).ok().map(|account| account.amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::program_option::COption;
+    use spl_token::state::AccountState;
+
+    #[test]
+    fn test_token_amount_reads_base_account() {
+        let account = TokenAccount {
+            mint: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            amount: 42,
+            delegate: COption::None,
+            state: AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::None,
+        };
+        let mut data = vec![0u8; TokenAccount::LEN];
+        TokenAccount::pack(account, &mut data).unwrap();
+        assert_eq!(token_amount(&data), Some(42));
+
+        // Extension bytes after the base layout are ignored
+        data.extend_from_slice(&[2, 0, 0, 0]);
+        assert_eq!(token_amount(&data), Some(42));
+
+        assert_eq!(token_amount(&[0u8; 10]), None);
+    }
+
+    #[test]
+    fn test_report_text_marks_failure() {
+        let report = SimulationReport {
+            error: Some("Error processing Instruction 3: custom program error: 0x1771".to_string()),
+            units_consumed: Some(120_000),
+            logs: vec!["Program log: slippage tolerance exceeded".to_string()],
+            balance_changes: vec![BalanceChange {
+                asset: "SOL".to_string(),
+                account: "wallet".to_string(),
+                pre: 1_000,
+                post: 995,
+            }],
+        };
+
+        let text = report.to_text();
+        assert!(!report.succeeded());
+        assert!(text.contains("would FAIL"));
+        assert!(text.contains("1000 → 995 (-5)"));
+        assert!(text.contains("slippage tolerance exceeded"));
+    }
+}
@@ -0,0 +1,311 @@
+use crate::jupiter::JupiterClient;
+use crate::utils::create_private_dir;
+use crate::{JupiterMcpError, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::RwLock;
+use std::time::{Duration, SystemTime};
+use tracing::{info, warn};
+
+/// Well-known tokens shipped with the binary so common symbols resolve offline
+const BUNDLED_TOKENS: &str = include_str!("../assets/tokens.json");
+
+/// How long a fetched token list is used before fetching it again
+const REFRESH_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How long to wait before fetching again after a failed fetch
+const RETRY_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Token metadata as published by the Jupiter token list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenInfo {
+    #[serde(alias = "id")]
+    pub address: String,
+    pub symbol: String,
+    #[serde(default)]
+    pub name: String,
+    pub decimals: u8,
+}
+
+/// Resolves token symbols to mints, backed by the bundled list, a disk
+/// cache and the Jupiter token API
+#[derive(Debug)]
+pub struct TokenRegistry {
+    cache_path: Option<PathBuf>,
+    state: RwLock<RegistryState>,
+}
+
+#[derive(Debug, Default)]
+struct RegistryState {
+    by_mint: HashMap<String, TokenInfo>,
+    /// Bundled symbols win over look-alikes from the full list
+    pinned: HashMap<String, String>,
+    refreshed_at: Option<SystemTime>,
+}
+
+impl TokenRegistry {
+    /// Seed from the bundled list, then layer the disk cache on top if present
+    pub fn new(cache_path: Option<PathBuf>) -> Self {
+        let bundled: Vec<TokenInfo> = serde_json::from_str(BUNDLED_TOKENS).expect("bundled token list is valid");
+
+        let mut state = RegistryState::default();
+        for token in bundled {
+            state.pinned.insert(token.symbol.to_uppercase(), token.address.clone());
+            state.by_mint.insert(token.address.clone(), token);
+        }
+
+        if let Some(path) = &cache_path {
+            match read_cache(path) {
+                Ok(Some((tokens, modified))) => {
+                    state.merge(tokens);
+                    state.refreshed_at = Some(modified);
+                }
+                Ok(None) => {}
+                Err(e) => warn!("Ignoring token cache {}: {}", path.display(), e),
+            }
+        }
+
+        Self {
+            cache_path,
+            state: RwLock::new(state),
+        }
+    }
+
+    /// Metadata for a mint, if the registry knows it
+    pub fn get(&self, mint: &str) -> Option<TokenInfo> {
+        self.state.read().unwrap().by_mint.get(mint).cloned()
+    }
+
+    /// Display label for a mint: its symbol when known, otherwise the mint
+    pub fn label(&self, mint: &str) -> String {
+        self.get(mint).map(|token| token.symbol).unwrap_or_else(|| mint.to_string())
+    }
+
+    /// Resolve a mint address or a symbol such as "USDC" to a mint address.
+    /// Unknown symbols trigger a refresh of a stale token list.
+    pub async fn resolve_mint(&self, jupiter: &JupiterClient, token: &str) -> Result<String> {
+        let token = token.trim();
+        if let Ok(mint) = Pubkey::from_str(token) {
+            return Ok(mint.to_string());
+        }
+
+        if let Some(mint) = self.find_symbol(token)? {
+            return Ok(mint);
+        }
+
+        if self.is_stale() {
+            match self.refresh(jupiter).await {
+                Ok(()) => {
+                    if let Some(mint) = self.find_symbol(token)? {
+                        return Ok(mint);
+                    }
+                }
+                Err(e) => warn!("Failed to refresh the Jupiter token list: {}", e),
+            }
+        }
+
+        Err(JupiterMcpError::InvalidInput(format!(
+            "Unknown token '{}'; pass a mint address or a symbol from the Jupiter token list",
+            token
+        )))
+    }
+
+    /// Fetch the Jupiter token list and write it to the disk cache
+    pub async fn refresh(&self, jupiter: &JupiterClient) -> Result<()> {
+        let tokens = match jupiter.tokens().await {
+            Ok(tokens) => tokens,
+            Err(e) => {
+                // Count the attempt, so an outage is not hit on every unknown symbol
+                self.state.write().unwrap().refreshed_at = SystemTime::now().checked_sub(REFRESH_INTERVAL - RETRY_INTERVAL);
+                return Err(e);
+            }
+        };
+        info!("Fetched {} tokens from the Jupiter token list", tokens.len());
+
+        if let Some(path) = &self.cache_path {
+            if let Err(e) = write_cache(path, &tokens) {
+                warn!("Failed to write token cache {}: {}", path.display(), e);
+            }
+        }
+
+        let mut state = self.state.write().unwrap();
+        state.merge(tokens);
+        state.refreshed_at = Some(SystemTime::now());
+        Ok(())
+    }
+
+    fn is_stale(&self) -> bool {
+        match self.state.read().unwrap().refreshed_at {
+            Some(at) => at.elapsed().map(|age| age > REFRESH_INTERVAL).unwrap_or(true),
+            None => true,
+        }
+    }
+
+    fn find_symbol(&self, symbol: &str) -> Result<Option<String>> {
+        let state = self.state.read().unwrap();
+        let key = symbol.to_uppercase();
+        if let Some(mint) = state.pinned.get(&key) {
+            return Ok(Some(mint.clone()));
+        }
+
+        let mut matches: Vec<&str> = state
+            .by_mint
+            .values()
+            .filter(|token| token.symbol.to_uppercase() == key)
+            .map(|token| token.address.as_str())
+            .collect();
+
+        match matches.len() {
+            0 => Ok(None),
+            1 => Ok(Some(matches[0].to_string())),
+            _ => {
+                matches.sort_unstable();
+                Err(JupiterMcpError::InvalidInput(format!(
+                    "Symbol '{}' matches several tokens, pass the mint instead: {}",
+                    symbol,
+                    matches.join(", ")
+                )))
+            }
+        }
+    }
+}
+
+impl RegistryState {
+    fn merge(&mut self, tokens: Vec<TokenInfo>) {
+        for token in tokens {
+            self.by_mint.insert(token.address.clone(), token);
+        }
+    }
+}
+
+fn read_cache(path: &Path) -> std::io::Result<Option<(Vec<TokenInfo>, SystemTime)>> {
+    let mut file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let metadata = file.metadata()?;
+    check_private(&metadata)?;
+
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)?;
+    let tokens = serde_json::from_str(&contents)?;
+    Ok(Some((tokens, metadata.modified()?)))
+}
+
+/// The cache decides which mint a symbol resolves to, so refuse one that
+/// another local user owns or could have written
+#[cfg(unix)]
+fn check_private(metadata: &std::fs::Metadata) -> std::io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    // SAFETY: geteuid has no preconditions and always succeeds
+    let uid = unsafe { libc::geteuid() };
+    if metadata.uid() != uid || metadata.mode() & 0o022 != 0 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            "owned by another user or writable by group or others",
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn check_private(_metadata: &std::fs::Metadata) -> std::io::Result<()> {
+    Ok(())
+}
+
+fn write_cache(path: &Path, tokens: &[TokenInfo]) -> std::io::Result<()> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        create_private_dir(dir)?;
+    }
+    // Write then rename so a crash never leaves a truncated cache behind
+    let partial = path.with_extension("partial");
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(&partial)?.write_all(&serde_json::to_vec(tokens)?)?;
+    std::fs::rename(partial, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::JupiterConfig;
+
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    fn offline_client() -> JupiterClient {
+        JupiterClient::new(&JupiterConfig {
+            token_api_url: "http://127.0.0.1:9".to_string(),
+            ..JupiterConfig::default()
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_bundled_symbols_resolve_offline() {
+        let registry = TokenRegistry::new(None);
+        let jupiter = offline_client();
+
+        assert_eq!(registry.resolve_mint(&jupiter, "usdc").await.unwrap(), USDC);
+        assert_eq!(registry.resolve_mint(&jupiter, USDC).await.unwrap(), USDC);
+        assert_eq!(registry.get(USDC).unwrap().decimals, 6);
+        assert!(registry.resolve_mint(&jupiter, "NOTATOKEN").await.is_err());
+
+        // The failed refresh counts, so the next unknown symbol does not refetch
+        assert!(!registry.is_stale());
+    }
+
+    #[test]
+    fn test_cache_is_loaded_and_ambiguous_symbols_rejected() {
+        let path = std::env::temp_dir().join(format!("jup-mcp-tokens-{}.json", uuid::Uuid::new_v4()));
+        let token = |address: &str, symbol: &str| TokenInfo {
+            address: address.to_string(),
+            symbol: symbol.to_string(),
+            name: String::new(),
+            decimals: 6,
+        };
+        write_cache(&path, &[
+            token("AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA", "FOO"),
+            token("BBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBBB", "DUP"),
+            token("CCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCCC", "DUP"),
+            // A look-alike of a bundled symbol does not shadow it
+            token("DDDDDDDDDDDDDDDDDDDDDDDDDDDDDDDDDDDDDDDDDDDD", "USDC"),
+        ]).unwrap();
+
+        let registry = TokenRegistry::new(Some(path.clone()));
+        assert!(!registry.is_stale());
+        assert_eq!(registry.find_symbol("foo").unwrap().as_deref(), Some("AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA"));
+        assert_eq!(registry.find_symbol("USDC").unwrap().as_deref(), Some(USDC));
+        assert!(registry.find_symbol("DUP").is_err());
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_cache_writable_by_others_is_ignored() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!("jup-mcp-tokens-{}.json", uuid::Uuid::new_v4()));
+        write_cache(&path, &[TokenInfo {
+            address: "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA".to_string(),
+            symbol: "FOO".to_string(),
+            name: String::new(),
+            decimals: 6,
+        }]).unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o666)).unwrap();
+        let registry = TokenRegistry::new(Some(path.clone()));
+        assert_eq!(registry.find_symbol("FOO").unwrap(), None);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
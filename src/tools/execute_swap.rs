@@ -1,29 +1,81 @@
-use crate::{Config, JupiterMcpError, Result};
+use crate::{JupiterMcpError, Result};
+use crate::config::JupiterBackend;
+use crate::confirmation::{confirm_swap, Confirmation};
+use crate::journal::{JournalEntry, PolicyDecision, SwapStatus};
+use crate::landing::{fetch_transaction, send_and_confirm, LandingOutcome};
 use crate::mcp::{Tool, ToolInputSchema, ToolResponse};
-use crate::utils::{get_connection, load_wallet, get_explorer_url};
+use crate::policy::{ConfirmationFallback, PolicyViolation};
+use crate::priority_fee::{priority_fee_lamports, set_compute_unit_limit, PriorityFee};
+use crate::session::Session;
+use crate::settlement::{RealizedSwap, WalletDeltas};
+use crate::utils::{load_wallet, get_explorer_url, TokenAmount, SOL_DECIMALS};
+use crate::signing::sign_transaction;
+use crate::simulation::simulate_swap;
 use crate::tools::get_quote::QuoteResponse;
+use crate::tools::ToolContext;
+use base64::{Engine as _, engine::general_purpose};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use solana_sdk::{
-    signature::Signer,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
     transaction::VersionedTransaction,
 };
+use solana_transaction_status::EncodedConfirmedTransactionWithStatusMeta;
 use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
+use tracing::{info, warn};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SwapRequest {
-    #[serde(rename = "quoteResponse")]
-    pub quote_response: QuoteResponse,
+    #[serde(rename = "quoteId")]
+    pub quote_id: String,
     #[serde(rename = "userPublicKey")]
     pub user_public_key: Option<String>,
     #[serde(rename = "wrapAndUnwrapSol")]
     pub wrap_and_unwrap_sol: Option<bool>,
+    pub simulate: Option<bool>,
+    #[serde(rename = "priorityFee")]
+    pub priority_fee: Option<PriorityFee>,
+    #[serde(rename = "computeUnitLimit")]
+    pub compute_unit_limit: Option<u32>,
+    #[serde(rename = "prioritizationFeeLamports")]
+    pub prioritization_fee_lamports: Option<u64>,
+    #[serde(rename = "dynamicComputeUnitLimit")]
+    pub dynamic_compute_unit_limit: Option<bool>,
+}
+
+impl SwapRequest {
+    /// Whether any compute budget option was given
+    fn has_fee_options(&self) -> bool {
+        self.priority_fee.is_some()
+            || self.compute_unit_limit.is_some()
+            || self.prioritization_fee_lamports.is_some()
+            || self.dynamic_compute_unit_limit.is_some()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SwapResponse {
     #[serde(rename = "swapTransaction")]
     pub swap_transaction: String,
+    #[serde(rename = "lastValidBlockHeight")]
+    pub last_valid_block_height: Option<u64>,
+}
+
+/// Response of the Ultra `/execute` endpoint
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExecuteResponse {
+    pub status: String,
+    pub signature: Option<String>,
+    pub slot: Option<String>,
+    pub code: Option<i64>,
+    pub error: Option<String>,
+    #[serde(rename = "inputAmountResult")]
+    pub input_amount_result: Option<String>,
+    #[serde(rename = "outputAmountResult")]
+    pub output_amount_result: Option<String>,
 }
 
 pub struct ExecuteSwapTool;
@@ -36,92 +88,615 @@ impl ExecuteSwapTool {
             input_schema: ToolInputSchema {
                 schema_type: "object".to_string(),
                 properties: json!({
-                    "quoteResponse": {
-                        "type": "object",
-                        "description": "Quote response from get_quote tool"
+                    "quoteId": {
+                        "type": "string",
+                        "description": "The quoteId returned by get_quote. Quotes expire shortly after being issued and can only be executed once."
                     },
                     "userPublicKey": {
                         "type": "string",
-                        "description": "User public key (optional, defaults to wallet)"
+                        "description": "User public key (optional, defaults to wallet; v6 backend only)"
                     },
                     "wrapAndUnwrapSol": {
                         "type": "boolean",
-                        "description": "Whether to wrap/unwrap SOL (default: true)"
+                        "description": "Whether to wrap/unwrap SOL (default: true; v6 backend only)"
+                    },
+                    "simulate": {
+                        "type": "boolean",
+                        "description": "Build, sign and simulate the swap without broadcasting it, reporting compute units, logs and balance changes (default: false). The quote stays valid for a later execution."
+                    },
+                    "priorityFee": {
+                        "description": "Priority fee (v6 backend only): \"auto\" lets Jupiter estimate it, {\"microLamports\": n} sets a fixed compute unit price, {\"percentile\": p} uses the p-th percentile of recent fees paid on the route's pools",
+                        "oneOf": [
+                            { "type": "string", "enum": ["auto"] },
+                            {
+                                "type": "object",
+                                "properties": { "microLamports": { "type": "integer", "minimum": 0 } },
+                                "required": ["microLamports"]
+                            },
+                            {
+                                "type": "object",
+                                "properties": { "percentile": { "type": "integer", "minimum": 1, "maximum": 100 } },
+                                "required": ["percentile"]
+                            }
+                        ]
+                    },
+                    "computeUnitLimit": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "description": "Fixed compute unit limit for the transaction (v6 backend only). Without it, Jupiter sizes the limit from a simulation whenever a priority fee is set."
+                    },
+                    "prioritizationFeeLamports": {
+                        "type": "integer",
+                        "minimum": 0,
+                        "description": "Total priority fee in lamports, passed to /swap as Jupiter's prioritizationFeeLamports (v6 backend only; use instead of priorityFee)"
+                    },
+                    "dynamicComputeUnitLimit": {
+                        "type": "boolean",
+                        "description": "Raw Jupiter dynamicComputeUnitLimit option, passed through to /swap (v6 backend only)"
                     }
                 }),
-                required: Some(vec!["quoteResponse".to_string()]),
+                required: Some(vec!["quoteId".to_string()]),
             },
         }
     }
-    
-    pub async fn execute(config: &Config, args: Value) -> Result<ToolResponse> {
+
+    pub async fn execute(ctx: &ToolContext, session: &Session, args: Value) -> Result<ToolResponse> {
         let request: SwapRequest = serde_json::from_value(args)
             .map_err(|e| JupiterMcpError::InvalidInput(format!("Invalid arguments: {}", e)))?;
-        
-        let connection = get_connection(config);
-        let wallet = load_wallet(config)?;
-        
-        let user_public_key = request.user_public_key
-            .unwrap_or_else(|| wallet.pubkey().to_string());
-        
-        let wrap_and_unwrap_sol = request.wrap_and_unwrap_sol.unwrap_or(true);
-        
-        // Prepare swap request for Jupiter API
-        let mut swap_request_body = HashMap::new();
-        swap_request_body.insert("quoteResponse", serde_json::to_value(&request.quote_response)?);
-        swap_request_body.insert("userPublicKey", json!(user_public_key));
-        swap_request_body.insert("wrapAndUnwrapSol", json!(wrap_and_unwrap_sol));
-        
-        // Get swap transaction from Jupiter API
-        let client = reqwest::Client::new();
-        let response = client
-            .post("https://quote-api.jup.ag/v6/swap")
-            .header("Content-Type", "application/json")
-            .json(&swap_request_body)
-            .send()
-            .await?;
-        
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(JupiterMcpError::JupiterApi(
-                format!("Jupiter swap API error {}: {}", status, error_text)
+
+        if ctx.jupiter.backend() == JupiterBackend::Ultra && request.has_fee_options() {
+            return Err(JupiterMcpError::InvalidInput(
+                "Priority fee and compute unit options only apply to the v6 backend; Ultra sets them itself".to_string()
             ));
         }
-        
-        let swap_response: SwapResponse = response.json().await?;
-        
-        // Deserialize the transaction from Jupiter
-        use base64::{Engine as _, engine::general_purpose};
-        let transaction_bytes = general_purpose::STANDARD.decode(&swap_response.swap_transaction)
-            .map_err(|e| JupiterMcpError::SolanaSdk(format!("Failed to decode transaction: {}", e)))?;
-        
-        // Deserialize as VersionedTransaction
-        let transaction: VersionedTransaction = bincode::deserialize(&transaction_bytes)
-            .map_err(|e| JupiterMcpError::SolanaSdk(format!("Failed to deserialize transaction: {}", e)))?;
-        
-        // Send the transaction
-        use solana_client::rpc_config::RpcSendTransactionConfig;
-        let send_config = RpcSendTransactionConfig {
-            skip_preflight: false,
-            ..Default::default()
+
+        let wallet = load_wallet(&ctx.config)?;
+
+        if request.simulate.unwrap_or(false) {
+            // Simulating leaves the quote in the store so it can still be executed
+            let quote = ctx.quotes.get(&request.quote_id)?;
+            if let Err(violation) = ctx.policy.check(&quote) {
+                return Ok(policy_error(violation));
+            }
+            return simulate(ctx, &wallet, request, &quote).await;
+        }
+
+        // Check the policy before consuming the quote, so a blocked swap can
+        // be re-quoted within limits without losing anything
+        let quote_id = request.quote_id.clone();
+        let quote = ctx.quotes.get(&quote_id)?;
+        let mut decision = PolicyDecision::default();
+        let reject = |decision: PolicyDecision, error: &str| {
+            let mut entry = JournalEntry::new(&quote_id, &quote, decision, SwapStatus::Rejected);
+            entry.error = Some(error.to_string());
+            ctx.journal.record(&entry);
+        };
+
+        if let Err(violation) = ctx.policy.check(&quote) {
+            decision.violated(&violation);
+            reject(decision, &violation.to_string());
+            return Ok(policy_error(violation));
+        }
+
+        if let Some(reason) = ctx.policy.confirmation_reason(&quote) {
+            let policy = &ctx.policy.policy().confirmation;
+            decision.confirmation_reason = Some(reason.clone());
+            // Never wait past the quote's expiry, when an approval could no
+            // longer be executed
+            let remaining = match ctx.quotes.remaining(&quote_id) {
+                Ok(remaining) => remaining,
+                Err(e) => {
+                    let detail = e.to_string();
+                    reject(decision, &detail);
+                    return Ok(confirmation_error("expired", &reason, &detail));
+                }
+            };
+            let timeout = Duration::from_secs(policy.timeout_secs).min(remaining);
+            match confirm_swap(session, &quote, &reason, timeout).await {
+                Confirmation::Approved => {
+                    info!("User approved swap for quote {}", quote_id);
+                    decision.confirmation = Some("approved".to_string());
+                }
+                Confirmation::Declined(why) => {
+                    decision.confirmation = Some("declined".to_string());
+                    reject(decision, &why);
+                    return Ok(confirmation_error("declined", &reason, &why));
+                }
+                Confirmation::Unavailable => {
+                    decision.confirmation = Some("unavailable".to_string());
+                    match policy.fallback {
+                        ConfirmationFallback::Allow => {
+                            warn!("Executing swap without confirmation ({}): client cannot receive elicitation", reason)
+                        }
+                        ConfirmationFallback::Reject => {
+                            let detail = "the client does not support elicitation or has no event stream open to receive it, so the swap cannot be approved";
+                            reject(decision, detail);
+                            return Ok(confirmation_error("unavailable", &reason, detail));
+                        }
+                    }
+                }
+            }
+        }
+
+        // Re-check while reserving, as other swaps may have used the daily
+        // allowance while the user was deciding
+        let reservation = match ctx.policy.reserve(&quote) {
+            Ok(reservation) => reservation,
+            Err(violation) => {
+                decision.violated(&violation);
+                reject(decision, &violation.to_string());
+                return Ok(policy_error(violation));
+            }
+        };
+
+        // Only quotes issued by get_quote can be executed, and only once
+        let result = match ctx.quotes.take(&quote_id) {
+            Ok(quote) => match ctx.jupiter.backend() {
+                JupiterBackend::Ultra => execute_ultra(ctx, &wallet, &quote).await,
+                JupiterBackend::SwapV6 => execute_v6(ctx, &wallet, request, &quote).await,
+            },
+            Err(e) => Err(e),
+        };
+
+        // Errors and rejections happen before anything is sent and an expired
+        // blockhash can never land, so only then is the input certainly
+        // unspent. Any other outcome, including a lost /execute response,
+        // stays counted.
+        let nothing_sent = match &result {
+            Ok(outcome) => matches!(outcome.status, SwapStatus::Expired | SwapStatus::Rejected),
+            Err(_) => true,
         };
-        
-        let signature = connection.send_transaction_with_config(&transaction, send_config)?;
-        
-        // Confirm the transaction
-        connection.confirm_transaction(&signature)?;
-        
-        let explorer_url = get_explorer_url(&signature, config);
-        
-        let response_text = format!(
+        if nothing_sent {
+            ctx.policy.release(reservation);
+        }
+
+        let mut entry = JournalEntry::new(&quote_id, &quote, decision, SwapStatus::Error);
+        match &result {
+            Ok(outcome) => {
+                if let Some(violation) = &outcome.violation {
+                    entry.policy.violated(violation);
+                }
+                entry.status = outcome.status;
+                entry.signature = outcome.signature.clone();
+                entry.error = outcome.error.clone();
+                entry.realized = outcome.realized.clone();
+            }
+            Err(e) => entry.error = Some(e.to_string()),
+        }
+        ctx.journal.record(&entry);
+
+        result.map(|outcome| outcome.response)
+    }
+}
+
+/// A submitted swap: the tool response and what the journal records about it
+struct SwapOutcome {
+    response: ToolResponse,
+    status: SwapStatus,
+    signature: Option<String>,
+    error: Option<String>,
+    realized: Option<RealizedSwap>,
+    /// Policy rule the built transaction broke, so it was not sent
+    violation: Option<PolicyViolation>,
+}
+
+impl SwapOutcome {
+    fn rejected(violation: PolicyViolation) -> Self {
+        Self {
+            status: SwapStatus::Rejected,
+            signature: None,
+            error: Some(violation.to_string()),
+            realized: None,
+            response: policy_error(violation.clone()),
+            violation: Some(violation),
+        }
+    }
+
+    /// The transaction left the server but its fate is unknown, e.g. the
+    /// submitting request timed out
+    fn unknown(signature: &Signature, error: JupiterMcpError) -> Self {
+        let error = error.to_string();
+        Self {
+            response: ToolResponse::error(format!(
+                "Swap submitted, but its outcome is unknown: {}\n\
+                Signature: {}\n\
+                It may still land; check it with get_transaction_status before trying again.",
+                error,
+                signature
+            )).with_structured(json!({ "signature": signature.to_string(), "status": "unknown" })),
+            status: SwapStatus::Error,
+            signature: Some(signature.to_string()),
+            error: Some(error),
+            realized: None,
+            violation: None,
+        }
+    }
+}
+
+fn policy_error(violation: PolicyViolation) -> ToolResponse {
+    ToolResponse::error(violation.to_string()).with_structured(violation.to_json())
+}
+
+fn confirmation_error(status: &str, reason: &str, detail: &str) -> ToolResponse {
+    ToolResponse::error(format!("Swap not executed: confirmation {} ({}); {}", status, reason, detail))
+        .with_structured(json!({
+            "confirmation": {
+                "status": status,
+                "reason": reason,
+                "detail": detail,
+            }
+        }))
+}
+
+/// Build and sign the swap, then run it through `simulateTransaction` only
+async fn simulate(
+    ctx: &ToolContext,
+    wallet: &Keypair,
+    request: SwapRequest,
+    quote: &QuoteResponse,
+) -> Result<ToolResponse> {
+    let transaction = match ctx.jupiter.backend() {
+        JupiterBackend::Ultra => sign_ultra_transaction(wallet, quote)?,
+        JupiterBackend::SwapV6 => build_v6_transaction(ctx, wallet, &request, quote).await?.0,
+    };
+    if let Err(violation) = ctx.policy.check_priority_fee(priority_fee_lamports(&transaction)) {
+        return Ok(policy_error(violation));
+    }
+
+    let report = simulate_swap(
+        &ctx.rpc,
+        &wallet.pubkey(),
+        &quote.input_mint,
+        &quote.output_mint,
+        &transaction,
+    ).await?;
+
+    Ok(ToolResponse::text(report.to_text()).with_structured(serde_json::to_value(&report)?))
+}
+
+/// Sign the transaction attached to an Ultra order
+fn sign_ultra_transaction(wallet: &Keypair, quote: &QuoteResponse) -> Result<VersionedTransaction> {
+    let encoded_transaction = quote.transaction().ok_or_else(|| JupiterMcpError::InvalidInput(
+        "Quote has no transaction attached; request a new quote from get_quote for the server wallet".to_string()
+    ))?;
+
+    sign_transaction(decode_transaction(encoded_transaction)?, wallet)
+}
+
+/// Sign the order transaction locally and hand it to Ultra `/execute`.
+/// Errors are only returned before the transaction is handed over.
+async fn execute_ultra(
+    ctx: &ToolContext,
+    wallet: &Keypair,
+    quote: &QuoteResponse,
+) -> Result<SwapOutcome> {
+    let request_id = quote.request_id().ok_or_else(|| JupiterMcpError::InvalidInput(
+        "Quote has no requestId; it must come from the Ultra order endpoint".to_string()
+    ))?;
+
+    let transaction = sign_ultra_transaction(wallet, quote)?;
+    if let Err(violation) = ctx.policy.check_priority_fee(priority_fee_lamports(&transaction)) {
+        return Ok(SwapOutcome::rejected(violation));
+    }
+    let signature = transaction.signatures[0];
+    let encoded_transaction = encode_transaction(&transaction)?;
+
+    let execute_response = match ctx.jupiter.execute(&encoded_transaction, request_id).await {
+        Ok(execute_response) => execute_response,
+        Err(e) => return Ok(SwapOutcome::unknown(&signature, e)),
+    };
+
+    if execute_response.status != "Success" {
+        let error = format!(
+            "code {}: {}",
+            execute_response.code.map(|c| c.to_string()).unwrap_or_else(|| "unknown".to_string()),
+            execute_response.error.as_deref().unwrap_or("no error message"),
+        );
+        return Ok(SwapOutcome {
+            response: ToolResponse::error(format!(
+                "Swap failed ({}){}",
+                error,
+                execute_response.signature.as_deref()
+                    .map(|s| format!("\nSignature: {}", s))
+                    .unwrap_or_default()
+            )),
+            status: SwapStatus::Failed,
+            signature: Some(execute_response.signature.unwrap_or_else(|| signature.to_string())),
+            error: Some(error),
+            realized: None,
+            violation: None,
+        });
+    }
+
+    let mut response_text = format!(
+        "Swap executed successfully!\n\
+        Signature: {}\n\
+        Slot: {}\n\
+        Input amount: {}\n\
+        Output amount: {}\n\
+        Explorer: {}",
+        signature,
+        execute_response.slot.as_deref().unwrap_or("unknown"),
+        execute_response.input_amount_result.as_deref().unwrap_or(&quote.in_amount),
+        execute_response.output_amount_result.as_deref().unwrap_or("unknown"),
+        get_explorer_url(&signature, &ctx.config)
+    );
+
+    // Ultra lands the transaction itself; read what it did back from the chain
+    let realized = match fetch_transaction(&ctx.rpc, &signature).await {
+        Ok(transaction) => realized_swap(&transaction, wallet, quote),
+        Err(e) => {
+            warn!("Could not fetch executed swap {}: {}", signature, e);
+            None
+        }
+    };
+    if let Some(realized) = &realized {
+        response_text.push_str(&format!("\n\n{}", realized_text(ctx, realized)));
+    }
+
+    Ok(SwapOutcome {
+        response: ToolResponse::text(response_text).with_structured(json!({
+            "signature": signature.to_string(),
+            "slot": execute_response.slot,
+            "realized": realized,
+        })),
+        status: SwapStatus::Confirmed,
+        signature: Some(signature.to_string()),
+        error: None,
+        realized,
+        violation: None,
+    })
+}
+
+/// Work out what a landed swap actually spent and received
+fn realized_swap(
+    transaction: &EncodedConfirmedTransactionWithStatusMeta,
+    wallet: &Keypair,
+    quote: &QuoteResponse,
+) -> Option<RealizedSwap> {
+    let deltas = WalletDeltas::from_transaction(transaction, &wallet.pubkey())?;
+    RealizedSwap::new(quote, &deltas)
+}
+
+fn realized_text(ctx: &ToolContext, realized: &RealizedSwap) -> String {
+    realized.to_text(&ctx.tokens.label(&realized.input_mint), &ctx.tokens.label(&realized.output_mint))
+}
+
+/// Build the transaction through v6 `/swap` and broadcast it over RPC until
+/// it lands or its blockhash expires. Errors are only returned before the
+/// first broadcast.
+async fn execute_v6(
+    ctx: &ToolContext,
+    wallet: &Keypair,
+    request: SwapRequest,
+    quote: &QuoteResponse,
+) -> Result<SwapOutcome> {
+    let (transaction, last_valid_block_height) = build_v6_transaction(ctx, wallet, &request, quote).await?;
+    // Checked on the built transaction, as "auto" and lamport budgets only
+    // become a compute unit price once Jupiter has priced them
+    if let Err(violation) = ctx.policy.check_priority_fee(priority_fee_lamports(&transaction)) {
+        return Ok(SwapOutcome::rejected(violation));
+    }
+    let signature = transaction.signatures[0];
+
+    let landing = match send_and_confirm(&ctx.rpc, &transaction, last_valid_block_height).await {
+        Ok(landing) => landing,
+        Err(e) => return Ok(SwapOutcome::unknown(&signature, e)),
+    };
+    let explorer_url = get_explorer_url(&signature, &ctx.config);
+    let realized = match (&landing.outcome, &landing.transaction) {
+        (LandingOutcome::Confirmed { .. }, Some(transaction)) => realized_swap(transaction, wallet, quote),
+        _ => None,
+    };
+    let mut structured = serde_json::to_value(&landing).unwrap_or_default();
+    structured["realized"] = serde_json::to_value(&realized).unwrap_or_default();
+
+    let (status, error) = match &landing.outcome {
+        LandingOutcome::Confirmed { .. } => (SwapStatus::Confirmed, None),
+        LandingOutcome::Failed { error, .. } => (SwapStatus::Failed, Some(error.clone())),
+        LandingOutcome::Expired { .. } => (
+            SwapStatus::Expired,
+            Some("blockhash expired before the transaction landed".to_string()),
+        ),
+    };
+
+    let response = match &landing.outcome {
+        LandingOutcome::Confirmed { slot, fee, commitment } => ToolResponse::text(format!(
             "Swap executed successfully!\n\
             Signature: {}\n\
+            Slot: {}\n\
+            Fee: {}\n\
+            Commitment: {:?}\n\
+            Explorer: {}{}",
+            signature,
+            slot,
+            describe_fee(*fee),
+            commitment,
+            explorer_url,
+            realized.as_ref()
+                .map(|realized| format!("\n\n{}", realized_text(ctx, realized)))
+                .unwrap_or_default()
+        )),
+        LandingOutcome::Failed { slot, fee, commitment, error } => ToolResponse::error(format!(
+            "Swap failed on-chain: {}\n\
+            Signature: {}\n\
+            Slot: {}\n\
+            Fee: {}\n\
+            Commitment: {:?}\n\
             Explorer: {}",
+            error,
             signature,
+            slot,
+            describe_fee(*fee),
+            commitment,
             explorer_url
-        );
-        
-        Ok(ToolResponse::text(response_text))
+        )),
+        LandingOutcome::Expired { last_valid_block_height, block_height } => ToolResponse::error(format!(
+            "Swap expired: the transaction did not land before block height {} (now {}) and can no longer be included. \
+            Nothing was spent; request a new quote to try again.\n\
+            Signature: {}",
+            last_valid_block_height,
+            block_height,
+            signature
+        )),
+    };
+
+    Ok(SwapOutcome {
+        response: response.with_structured(structured),
+        status,
+        signature: Some(landing.signature),
+        error,
+        realized,
+        violation: None,
+    })
+}
+
+pub(crate) fn describe_fee(fee: Option<u64>) -> String {
+    fee.map(|lamports| format!("{} SOL ({} lamports)", TokenAmount::new(lamports, SOL_DECIMALS), lamports))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Request a v6 `/swap` transaction for the quote and sign it, returning it
+/// with the last block height at which its blockhash is valid
+async fn build_v6_transaction(
+    ctx: &ToolContext,
+    wallet: &Keypair,
+    request: &SwapRequest,
+    quote: &QuoteResponse,
+) -> Result<(VersionedTransaction, Option<u64>)> {
+    let user_public_key = request.user_public_key.clone()
+        .unwrap_or_else(|| wallet.pubkey().to_string());
+
+    let wrap_and_unwrap_sol = request.wrap_and_unwrap_sol.unwrap_or(true);
+
+    // Prepare swap request for Jupiter API
+    let mut swap_request_body = HashMap::new();
+    swap_request_body.insert("quoteResponse", serde_json::to_value(quote)?);
+    swap_request_body.insert("userPublicKey", json!(user_public_key));
+    swap_request_body.insert("wrapAndUnwrapSol", json!(wrap_and_unwrap_sol));
+    insert_fee_options(ctx, request, quote, &mut swap_request_body).await?;
+
+    // Get swap transaction from Jupiter API
+    let swap_response = ctx.jupiter.swap(&swap_request_body).await?;
+    let mut transaction = decode_transaction(&swap_response.swap_transaction)?;
+
+    // Jupiter has no fixed limit option, so rewrite its compute budget instruction
+    if let Some(units) = request.compute_unit_limit {
+        set_compute_unit_limit(&mut transaction, units)?;
+    }
+
+    // Sign with the configured wallet
+    let transaction = sign_transaction(transaction, wallet)?;
+    Ok((transaction, swap_response.last_valid_block_height))
+}
+
+/// Add the priority fee and compute unit options to a v6 `/swap` request body
+async fn insert_fee_options(
+    ctx: &ToolContext,
+    request: &SwapRequest,
+    quote: &QuoteResponse,
+    body: &mut HashMap<&str, Value>,
+) -> Result<()> {
+    if request.priority_fee.is_some() && request.prioritization_fee_lamports.is_some() {
+        return Err(JupiterMcpError::InvalidInput(
+            "Pass either priorityFee or prioritizationFeeLamports, not both".to_string()
+        ));
+    }
+    if request.compute_unit_limit.is_some() && request.dynamic_compute_unit_limit == Some(true) {
+        return Err(JupiterMcpError::InvalidInput(
+            "computeUnitLimit cannot be combined with dynamicComputeUnitLimit".to_string()
+        ));
+    }
+
+    if let Some(priority_fee) = &request.priority_fee {
+        match priority_fee.resolve(&ctx.rpc, &route_accounts(quote)).await? {
+            Some(micro_lamports) => {
+                info!("Using a priority fee of {} microLamports per compute unit", micro_lamports);
+                body.insert("computeUnitPriceMicroLamports", json!(micro_lamports));
+            }
+            None => {
+                body.insert("prioritizationFeeLamports", json!("auto"));
+            }
+        }
+    }
+    if let Some(lamports) = request.prioritization_fee_lamports {
+        body.insert("prioritizationFeeLamports", json!(lamports));
     }
-}
\ No newline at end of file
+
+    // A fee is charged per compute unit, so size the limit to the swap unless
+    // the caller fixed it
+    let has_fee = request.priority_fee.is_some() || request.prioritization_fee_lamports.is_some();
+    let dynamic = request.dynamic_compute_unit_limit
+        .unwrap_or(has_fee && request.compute_unit_limit.is_none());
+    if dynamic || request.dynamic_compute_unit_limit.is_some() {
+        body.insert("dynamicComputeUnitLimit", json!(dynamic));
+    }
+
+    Ok(())
+}
+
+/// Pools the quote routes through, whose recent fees set the percentile mode
+fn route_accounts(quote: &QuoteResponse) -> Vec<Pubkey> {
+    quote.route_plan
+        .iter()
+        .filter_map(|route| Pubkey::from_str(&route.swap_info.amm_key).ok())
+        .collect()
+}
+
+/// Decode a base64 wire transaction returned by Jupiter
+fn decode_transaction(encoded: &str) -> Result<VersionedTransaction> {
+    let transaction_bytes = general_purpose::STANDARD.decode(encoded)
+        .map_err(|e| JupiterMcpError::SolanaSdk(format!("Failed to decode transaction: {}", e)))?;
+
+    bincode::deserialize(&transaction_bytes)
+        .map_err(|e| JupiterMcpError::SolanaSdk(format!("Failed to deserialize transaction: {}", e)))
+}
+
+/// Encode a signed transaction as base64 wire format
+fn encode_transaction(transaction: &VersionedTransaction) -> Result<String> {
+    let transaction_bytes = bincode::serialize(transaction)
+        .map_err(|e| JupiterMcpError::SolanaSdk(format!("Failed to serialize transaction: {}", e)))?;
+
+    Ok(general_purpose::STANDARD.encode(transaction_bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{hash::Hash, message::{v0, VersionedMessage}, pubkey::Pubkey, system_instruction};
+
+    #[test]
+    fn test_transaction_encoding_round_trip() {
+        let wallet = Keypair::new();
+        let instruction = system_instruction::transfer(&wallet.pubkey(), &Pubkey::new_unique(), 1);
+        let message = v0::Message::try_compile(&wallet.pubkey(), &[instruction], &[], Hash::new_unique()).unwrap();
+        let transaction = VersionedTransaction::try_new(VersionedMessage::V0(message), &[&wallet]).unwrap();
+
+        let decoded = decode_transaction(&encode_transaction(&transaction).unwrap()).unwrap();
+        assert_eq!(decoded, transaction);
+    }
+
+    #[test]
+    fn test_unknown_outcome_keeps_signature() {
+        let signature = Signature::from([3; 64]);
+        let outcome = SwapOutcome::unknown(&signature, JupiterMcpError::JupiterApi("timed out".to_string()));
+
+        assert_eq!(outcome.status, SwapStatus::Error);
+        assert_eq!(outcome.signature, Some(signature.to_string()));
+        assert!(outcome.response.is_error.unwrap_or(false));
+    }
+
+    #[test]
+    fn test_execute_response_deserialization() {
+        let response: ExecuteResponse = serde_json::from_value(json!({
+            "status": "Failed",
+            "code": -1005,
+            "error": "Transaction expired",
+            "signature": "5xN9ZJ1ShQbUXdbuGZMF1w5o3X7zjSuZrJ5Q1oZq9pXb"
+        })).unwrap();
+
+        assert_eq!(response.status, "Failed");
+        assert_eq!(response.code, Some(-1005));
+        assert!(response.slot.is_none());
+    }
+}
@@ -1,20 +1,35 @@
-use crate::{Config, JupiterMcpError, Result};
+use crate::{JupiterMcpError, Result};
 use crate::mcp::{Tool, ToolInputSchema, ToolResponse};
-use crate::utils::{get_connection, parse_pubkey, format_sol, format_token_amount};
+use crate::mint::get_mint_info;
+use crate::tools::ToolContext;
+use crate::utils::{load_wallet, parse_pubkey, TokenAmount, SOL_DECIMALS};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use solana_client::rpc_client::RpcClient;
-use solana_sdk::{pubkey::Pubkey, program_pack::Pack};
-use spl_token::state::{Account as TokenAccount, Mint};
+use solana_account_decoder::UiAccountData;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_request::TokenAccountsFilter;
+use solana_client::rpc_response::RpcKeyedAccount;
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BalanceRequest {
     #[serde(rename = "walletAddress")]
-    pub wallet_address: String,
+    pub wallet_address: Option<String>,
     #[serde(rename = "tokenMint")]
     pub token_mint: Option<String>,
 }
 
+/// A token account held by a wallet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenHolding {
+    pub mint: String,
+    pub account: String,
+    /// Token program owning the account (SPL Token or Token-2022)
+    pub program: String,
+    pub amount: u64,
+    pub decimals: u8,
+}
+
 pub struct GetBalanceTool;
 
 impl GetBalanceTool {
@@ -27,82 +42,126 @@ impl GetBalanceTool {
                 properties: json!({
                     "walletAddress": {
                         "type": "string",
-                        "description": "Wallet address to check balance for"
+                        "description": "Wallet address to check balance for (optional, defaults to the server wallet)"
                     },
                     "tokenMint": {
                         "type": "string",
-                        "description": "Token mint address (optional, omit for SOL balance)"
+                        "description": "Token mint address or symbol such as \"USDC\" (optional, omit or pass \"SOL\" for the native SOL balance)"
                     }
                 }),
-                required: Some(vec!["walletAddress".to_string()]),
+                required: None,
             },
         }
     }
     
-    pub async fn execute(config: &Config, args: Value) -> Result<ToolResponse> {
+    pub async fn execute(ctx: &ToolContext, args: Value) -> Result<ToolResponse> {
         let request: BalanceRequest = serde_json::from_value(args)
             .map_err(|e| JupiterMcpError::InvalidInput(format!("Invalid arguments: {}", e)))?;
         
-        let connection = get_connection(config);
-        let wallet_pubkey = parse_pubkey(&request.wallet_address)?;
+        let wallet_pubkey = match &request.wallet_address {
+            Some(address) => parse_pubkey(address)?,
+            None => load_wallet(&ctx.config)?.pubkey(),
+        };
+        
+        // "SOL" means the native balance rather than the wrapped SOL mint
+        let token_mint = request.token_mint.filter(|token| !token.trim().eq_ignore_ascii_case("SOL"));
         
-        match request.token_mint {
+        match token_mint {
             None => {
                 // Get SOL balance
-                let balance = connection.get_balance(&wallet_pubkey)?;
-                let formatted_balance = format_sol(balance);
+                let balance = ctx.rpc.get_balance(&wallet_pubkey).await?;
+                let formatted_balance = TokenAmount::new(balance, SOL_DECIMALS);
                 Ok(ToolResponse::text(format!("SOL Balance: {} SOL", formatted_balance)))
             }
             Some(mint_address) => {
                 // Get SPL token balance
+                let mint_address = ctx.tokens.resolve_mint(&ctx.jupiter, &mint_address).await?;
                 let mint_pubkey = parse_pubkey(&mint_address)?;
-                let balance_result = get_token_balance(&connection, &wallet_pubkey, &mint_pubkey)?;
+                let mint_info = get_mint_info(&ctx.rpc, &mint_pubkey).await?;
+                let balance_result = get_token_balance(&ctx.rpc, &wallet_pubkey, &mint_pubkey).await?;
                 
-                match balance_result {
-                    Some((balance, decimals)) => {
-                        let formatted_balance = format_token_amount(balance, decimals);
-                        Ok(ToolResponse::text(format!("Token Balance: {}", formatted_balance)))
+                let mut text = match balance_result {
+                    Some(balance) => {
+                        let mut text = format!("Token Balance: {}", TokenAmount::new(balance, mint_info.decimals));
+                        if let Some(with_interest) = mint_info.ui_amount_with_interest(balance) {
+                            text.push_str(&format!("\nWith accrued interest: {}", with_interest));
+                        }
+                        text
                     }
-                    None => {
-                        Ok(ToolResponse::text("Token account not found - Balance: 0".to_string()))
+                    None => "Token account not found - Balance: 0".to_string(),
+                };
+                
+                if mint_info.is_token_2022() {
+                    text.push_str("\nProgram: Token-2022");
+                    for extension in mint_info.describe_extensions() {
+                        text.push_str(&format!("\n- {}", extension));
                     }
                 }
+                
+                Ok(ToolResponse::text(text))
             }
         }
     }
 }
 
-fn get_token_balance(
+/// Total balance across every account the wallet holds for a mint, under
+/// either token program
+async fn get_token_balance(
     connection: &RpcClient,
     wallet_pubkey: &Pubkey,
     mint_pubkey: &Pubkey,
-) -> Result<Option<(u64, u8)>> {
-    // Get all token accounts for this wallet filtered by mint
+) -> Result<Option<u64>> {
     let token_accounts = connection.get_token_accounts_by_owner(
         wallet_pubkey,
-        solana_client::rpc_request::TokenAccountsFilter::Mint(*mint_pubkey),
-    )?;
+        TokenAccountsFilter::Mint(*mint_pubkey),
+    ).await?;
     
     if token_accounts.is_empty() {
         return Ok(None);
     }
     
-    // Get the first (and should be only) token account
-    let token_account_pubkey = parse_pubkey(&token_accounts[0].pubkey)?;
-    
-    // Get account data
-    let account_data = connection.get_account_data(&token_account_pubkey)?;
+    let mut total: u64 = 0;
+    for keyed_account in &token_accounts {
+        total = total.saturating_add(parse_token_holding(keyed_account)?.amount);
+    }
+    Ok(Some(total))
+}
+
+/// List every token account owned by a wallet under both SPL Token and Token-2022
+pub(crate) async fn get_token_holdings(
+    connection: &RpcClient,
+    wallet_pubkey: &Pubkey,
+) -> Result<Vec<TokenHolding>> {
+    let (token_accounts, token_2022_accounts) = tokio::try_join!(
+        connection.get_token_accounts_by_owner(wallet_pubkey, TokenAccountsFilter::ProgramId(spl_token::id())),
+        connection.get_token_accounts_by_owner(wallet_pubkey, TokenAccountsFilter::ProgramId(spl_token_2022::id())),
+    )?;
     
-    // Parse token account
-    let token_account = TokenAccount::unpack(&account_data)
-        .map_err(|e| JupiterMcpError::SolanaSdk(format!("Failed to parse token account: {}", e)))?;
+    token_accounts.iter().chain(&token_2022_accounts).map(parse_token_holding).collect()
+}
+
+/// Read a jsonParsed token account as returned by `getTokenAccountsByOwner`
+fn parse_token_holding(keyed_account: &RpcKeyedAccount) -> Result<TokenHolding> {
+    let parsed = match &keyed_account.account.data {
+        UiAccountData::Json(parsed) => &parsed.parsed,
+        _ => return Err(JupiterMcpError::SolanaSdk(
+            format!("Token account {} was not returned as parsed JSON", keyed_account.pubkey)
+        )),
+    };
     
-    // Get mint info for decimals
-    let mint_data = connection.get_account_data(mint_pubkey)?;
-    let mint = Mint::unpack(&mint_data)
-        .map_err(|e| JupiterMcpError::SolanaSdk(format!("Failed to parse mint: {}", e)))?;
+    let info = &parsed["info"];
+    let token_amount = &info["tokenAmount"];
+    let invalid = || JupiterMcpError::SolanaSdk(
+        format!("Unexpected token account layout for {}", keyed_account.pubkey)
+    );
     
-    Ok(Some((token_account.amount, mint.decimals)))
+    Ok(TokenHolding {
+        mint: info["mint"].as_str().ok_or_else(invalid)?.to_string(),
+        account: keyed_account.pubkey.clone(),
+        program: keyed_account.account.owner.clone(),
+        amount: token_amount["amount"].as_str().and_then(|a| a.parse().ok()).ok_or_else(invalid)?,
+        decimals: token_amount["decimals"].as_u64().and_then(|d| u8::try_from(d).ok()).ok_or_else(invalid)?,
+    })
 }
 
 #[cfg(test)]
@@ -116,8 +175,11 @@ mod tests {
         });
         
         let request: BalanceRequest = serde_json::from_value(json).unwrap();
-        assert_eq!(request.wallet_address, "11111111111111111111111111111112");
+        assert_eq!(request.wallet_address.as_deref(), Some("11111111111111111111111111111112"));
         assert!(request.token_mint.is_none());
+        
+        let request: BalanceRequest = serde_json::from_value(json!({})).unwrap();
+        assert!(request.wallet_address.is_none());
     }
     
     #[test]
@@ -128,7 +190,38 @@ mod tests {
         });
         
         let request: BalanceRequest = serde_json::from_value(json).unwrap();
-        assert_eq!(request.wallet_address, "11111111111111111111111111111112");
+        assert_eq!(request.wallet_address.as_deref(), Some("11111111111111111111111111111112"));
         assert_eq!(request.token_mint.unwrap(), "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v");
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_parse_token_holding() {
+        let keyed_account: RpcKeyedAccount = serde_json::from_value(json!({
+            "pubkey": "3emsAVdmGKERbHjmGfQ6oZ1e35dkf5iYcS6U4CPKFVaa",
+            "account": {
+                "lamports": 2039280,
+                "owner": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA",
+                "executable": false,
+                "rentEpoch": 0,
+                "data": {
+                    "program": "spl-token",
+                    "space": 165,
+                    "parsed": {
+                        "type": "account",
+                        "info": {
+                            "mint": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+                            "owner": "11111111111111111111111111111112",
+                            "tokenAmount": { "amount": "2500000", "decimals": 6, "uiAmountString": "2.5" }
+                        }
+                    }
+                }
+            }
+        })).unwrap();
+        
+        let holding = parse_token_holding(&keyed_account).unwrap();
+        assert_eq!(holding.mint, "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v");
+        assert_eq!(holding.amount, 2_500_000);
+        assert_eq!(holding.decimals, 6);
+        assert_eq!(holding.program, spl_token::id().to_string());
+    }
+}
@@ -0,0 +1,282 @@
+use crate::{JupiterMcpError, Result};
+use crate::mcp::{Tool, ToolInputSchema, ToolResponse};
+use crate::mint::{get_mint_info, MintInfo};
+use crate::tools::get_balance::{get_token_holdings, TokenHolding};
+use crate::tools::ToolContext;
+use crate::utils::{load_wallet, parse_pubkey, TokenAmount, SOL_DECIMALS};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use solana_sdk::signature::Signer;
+use std::collections::BTreeMap;
+use tracing::warn;
+
+/// Mint Jupiter prices native SOL under
+const WRAPPED_SOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PortfolioRequest {
+    #[serde(rename = "walletAddress")]
+    pub wallet_address: Option<String>,
+    #[serde(rename = "includeUsd")]
+    pub include_usd: Option<bool>,
+    #[serde(rename = "includeEmpty")]
+    pub include_empty: Option<bool>,
+}
+
+/// One mint's total across every token account the wallet holds
+#[derive(Debug, Clone, PartialEq)]
+pub struct Position {
+    pub mint: String,
+    pub symbol: Option<String>,
+    pub amount: TokenAmount,
+    pub accounts: Vec<String>,
+    pub program: String,
+    pub usd_value: Option<f64>,
+    /// Decoded mint, looked up for Token-2022 positions
+    pub mint_info: Option<MintInfo>,
+}
+
+impl Position {
+    fn to_json(&self) -> Value {
+        json!({
+            "mint": self.mint,
+            "symbol": self.symbol,
+            "amount": self.amount.raw().to_string(),
+            "decimals": self.amount.decimals(),
+            "uiAmount": self.amount.to_plain_string(),
+            "accounts": self.accounts,
+            "program": self.program,
+            "usdValue": self.usd_value,
+            "extensions": self.mint_info.as_ref().map(MintInfo::describe_extensions),
+            "uiAmountWithInterest": self.amount_with_interest(),
+        })
+    }
+
+    fn is_token_2022(&self) -> bool {
+        self.program == spl_token_2022::id().to_string()
+    }
+
+    /// Balance with accrued interest, for interest-bearing mints
+    fn amount_with_interest(&self) -> Option<String> {
+        let amount = u64::try_from(self.amount.raw()).ok()?;
+        self.mint_info.as_ref()?.ui_amount_with_interest(amount)
+    }
+}
+
+pub struct GetPortfolioTool;
+
+impl GetPortfolioTool {
+    pub fn definition() -> Tool {
+        Tool {
+            name: "get_portfolio".to_string(),
+            description: "List a wallet's SOL and every SPL Token and Token-2022 holding, summed per mint, optionally valued in USD".to_string(),
+            input_schema: ToolInputSchema {
+                schema_type: "object".to_string(),
+                properties: json!({
+                    "walletAddress": {
+                        "type": "string",
+                        "description": "Wallet to list (optional, defaults to the server wallet)"
+                    },
+                    "includeUsd": {
+                        "type": "boolean",
+                        "description": "Value holdings in USD with the Jupiter Price API (default: false)"
+                    },
+                    "includeEmpty": {
+                        "type": "boolean",
+                        "description": "Also list mints whose accounts are all empty (default: false)"
+                    }
+                }),
+                required: None,
+            },
+        }
+    }
+
+    pub async fn execute(ctx: &ToolContext, args: Value) -> Result<ToolResponse> {
+        let request: PortfolioRequest = serde_json::from_value(args)
+            .map_err(|e| JupiterMcpError::InvalidInput(format!("Invalid arguments: {}", e)))?;
+
+        let wallet = match &request.wallet_address {
+            Some(address) => parse_pubkey(address)?,
+            None => load_wallet(&ctx.config)?.pubkey(),
+        };
+
+        let (lamports, holdings) = tokio::try_join!(
+            async { Ok(ctx.rpc.get_balance(&wallet).await?) },
+            get_token_holdings(&ctx.rpc, &wallet),
+        )?;
+
+        let mut positions = aggregate(holdings);
+        if !request.include_empty.unwrap_or(false) {
+            positions.retain(|position| position.amount.raw() > 0);
+        }
+        for position in &mut positions {
+            position.symbol = ctx.tokens.get(&position.mint).map(|token| token.symbol);
+        }
+        describe_token_2022_mints(ctx, &mut positions).await;
+
+        let sol = TokenAmount::new(lamports, SOL_DECIMALS);
+        let mut sol_value = None;
+
+        if request.include_usd.unwrap_or(false) {
+            let mut mints: Vec<String> = positions.iter().map(|position| position.mint.clone()).collect();
+            mints.push(WRAPPED_SOL_MINT.to_string());
+            mints.sort();
+            mints.dedup();
+
+            // Prices are a convenience, so a Price API failure still returns balances
+            match ctx.jupiter.prices(&mints).await {
+                Ok(prices) => {
+                    sol_value = prices.get(WRAPPED_SOL_MINT).map(|price| usd_value(sol, *price));
+                    for position in &mut positions {
+                        position.usd_value = prices.get(&position.mint).map(|price| usd_value(position.amount, *price));
+                    }
+                }
+                Err(e) => warn!("Failed to fetch USD prices: {}", e),
+            }
+
+            positions.sort_by(|a, b| b.usd_value.unwrap_or(-1.0).total_cmp(&a.usd_value.unwrap_or(-1.0)));
+        }
+
+        let total_usd = sol_value.map(|sol_value| {
+            sol_value + positions.iter().filter_map(|position| position.usd_value).sum::<f64>()
+        });
+
+        let mut text = format!("💼 Portfolio of {}\n\nSOL: {}{}\n", wallet, sol, format_usd(sol_value));
+        if positions.is_empty() {
+            text.push_str("\nNo token holdings.\n");
+        } else {
+            text.push_str(&format!("\nTokens ({}):\n", positions.len()));
+            for position in &positions {
+                text.push_str(&format!(
+                    "  {} {}{}{}\n",
+                    position.amount,
+                    position.symbol.as_deref().unwrap_or(&position.mint),
+                    if position.is_token_2022() { " [Token-2022]" } else { "" },
+                    format_usd(position.usd_value),
+                ));
+                if let Some(with_interest) = position.amount_with_interest() {
+                    text.push_str(&format!("    With accrued interest: {}\n", with_interest));
+                }
+                for extension in position.mint_info.iter().flat_map(MintInfo::describe_extensions) {
+                    text.push_str(&format!("    - {}\n", extension));
+                }
+            }
+        }
+        if let Some(total) = total_usd {
+            text.push_str(&format!("\nTotal value: ${:.2} (unpriced tokens excluded)\n", total));
+        }
+
+        let structured = json!({
+            "wallet": wallet.to_string(),
+            "sol": {
+                "lamports": lamports.to_string(),
+                "uiAmount": sol.to_plain_string(),
+                "usdValue": sol_value,
+            },
+            "tokens": positions.iter().map(Position::to_json).collect::<Vec<_>>(),
+            "totalUsdValue": total_usd,
+        });
+
+        Ok(ToolResponse::text(text).with_structured(structured))
+    }
+}
+
+/// Decode the mints of Token-2022 positions, whose extensions such as
+/// transfer fees and interest change what the holding is worth
+async fn describe_token_2022_mints(ctx: &ToolContext, positions: &mut [Position]) {
+    let lookups = positions.iter().filter(|position| position.is_token_2022()).map(|position| async move {
+        let mint = parse_pubkey(&position.mint).ok()?;
+        match get_mint_info(&ctx.rpc, &mint).await {
+            Ok(info) => Some(info),
+            Err(e) => {
+                warn!("Failed to read Token-2022 mint {}: {}", mint, e);
+                None
+            }
+        }
+    });
+    let infos = futures::future::join_all(lookups).await;
+
+    for (position, info) in positions.iter_mut().filter(|position| position.is_token_2022()).zip(infos) {
+        position.mint_info = info;
+    }
+}
+
+/// Sum token accounts per mint, ordered by mint
+fn aggregate(holdings: Vec<TokenHolding>) -> Vec<Position> {
+    let mut by_mint: BTreeMap<String, Position> = BTreeMap::new();
+    for holding in holdings {
+        let position = by_mint.entry(holding.mint.clone()).or_insert_with(|| Position {
+            mint: holding.mint.clone(),
+            symbol: None,
+            amount: TokenAmount::new(0u64, holding.decimals),
+            accounts: Vec::new(),
+            program: holding.program.clone(),
+            usd_value: None,
+            mint_info: None,
+        });
+        position.amount = TokenAmount::new(position.amount.raw() + holding.amount as u128, holding.decimals);
+        position.accounts.push(holding.account);
+    }
+    by_mint.into_values().collect()
+}
+
+fn usd_value(amount: TokenAmount, price: f64) -> f64 {
+    amount.to_plain_string().parse::<f64>().unwrap_or(0.0) * price
+}
+
+fn format_usd(value: Option<f64>) -> String {
+    value.map(|value| format!(" (${:.2})", value)).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn holding(mint: &str, account: &str, amount: u64) -> TokenHolding {
+        TokenHolding {
+            mint: mint.to_string(),
+            account: account.to_string(),
+            program: spl_token::id().to_string(),
+            amount,
+            decimals: 6,
+        }
+    }
+
+    #[test]
+    fn test_aggregate_sums_accounts_per_mint() {
+        let positions = aggregate(vec![
+            holding("mintB", "acc1", 1_500_000),
+            holding("mintA", "acc2", 0),
+            holding("mintB", "acc3", u64::MAX),
+        ]);
+
+        assert_eq!(positions.len(), 2);
+        assert_eq!(positions[0].mint, "mintA");
+        assert_eq!(positions[1].accounts, vec!["acc1", "acc3"]);
+        // Totals are u128, so several large accounts cannot overflow
+        assert_eq!(positions[1].amount.raw(), u64::MAX as u128 + 1_500_000);
+    }
+
+    #[test]
+    fn test_token_2022_position_lists_extensions() {
+        let mut position = aggregate(vec![holding("mintA", "acc1", 1_000_000)]).remove(0);
+        position.program = spl_token_2022::id().to_string();
+        position.mint_info = Some(MintInfo {
+            address: solana_sdk::pubkey::Pubkey::new_unique(),
+            program: spl_token_2022::id(),
+            decimals: 6,
+            extensions: vec![crate::mint::MintExtension::TransferFee { basis_points: 100, maximum_fee: 5_000_000 }],
+        });
+
+        let json = position.to_json();
+        assert!(json["extensions"][0].as_str().unwrap().contains("transfer fee of 100 bps"));
+        assert_eq!(json["uiAmountWithInterest"], Value::Null);
+    }
+
+    #[test]
+    fn test_usd_value() {
+        assert_eq!(usd_value(TokenAmount::new(2_500_000u64, 6), 2.0), 5.0);
+        assert_eq!(format_usd(Some(1234.567)), " ($1234.57)");
+        assert_eq!(format_usd(None), "");
+    }
+}
@@ -1,8 +1,12 @@
 use crate::mcp::{Tool, ToolInputSchema, ToolResponse};
-use crate::utils::parse_pubkey;
-use crate::{Config, JupiterMcpError, Result};
+use crate::config::JupiterBackend;
+use crate::mint::{get_mint_info, MintInfo};
+use crate::utils::{load_wallet, parse_amount, parse_pubkey, TokenAmount};
+use crate::tools::ToolContext;
+use crate::{JupiterMcpError, Result};
 use serde::{Deserialize, Serialize};
-use serde_json::{json, Value};
+use serde_json::{json, Map, Value};
+use solana_sdk::signature::Signer;
 use std::collections::HashMap;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -12,14 +16,26 @@ pub struct QuoteRequest {
     #[serde(rename = "outputMint")]
     pub output_mint: String,
     pub amount: String,
-    pub taker: String,
+    #[serde(rename = "amountUnits")]
+    pub amount_units: Option<AmountUnits>,
+    pub taker: Option<String>,
     #[serde(rename = "swapMode")]
     pub swap_mode: Option<String>,
     #[serde(rename = "slippageBps")]
     pub slippage_bps: Option<u16>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// What `amount` is counted in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AmountUnits {
+    /// The token's smallest unit, e.g. lamports
+    Base,
+    /// Whole tokens, e.g. SOL
+    Ui,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QuoteResponse {
     #[serde(rename = "inputMint")]
     pub input_mint: String,
@@ -39,16 +55,38 @@ pub struct QuoteResponse {
     pub price_impact_pct: String,
     #[serde(rename = "routePlan")]
     pub route_plan: Vec<RoutePlan>,
+    /// Fields not modelled above, kept so the quote round-trips unchanged
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl QuoteResponse {
+    /// Unsigned transaction attached by Ultra `/order` when a taker is given
+    pub fn transaction(&self) -> Option<&str> {
+        self.extra.get("transaction").and_then(Value::as_str)
+    }
+    
+    /// Ultra order id that must accompany the signed transaction to `/execute`
+    pub fn request_id(&self) -> Option<&str> {
+        self.extra.get("requestId").and_then(Value::as_str)
+    }
+    
+    /// Reason Ultra gave for not attaching a transaction, if any
+    pub fn error_message(&self) -> Option<&str> {
+        self.extra.get("errorMessage").and_then(Value::as_str)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RoutePlan {
     #[serde(rename = "swapInfo")]
     pub swap_info: SwapInfo,
     pub percent: u8,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SwapInfo {
     #[serde(rename = "ammKey")]
     pub amm_key: String,
@@ -65,6 +103,8 @@ pub struct SwapInfo {
     pub fee_amount: String,
     #[serde(rename = "feeMint")]
     pub fee_mint: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 pub struct GetQuoteTool;
@@ -79,19 +119,24 @@ impl GetQuoteTool {
                 properties: json!({
                     "inputMint": {
                         "type": "string",
-                        "description": "The token address (mint) you want to swap FROM (e.g., USDC: EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v)"
+                        "description": "The token you want to swap FROM, as a mint address or a symbol (e.g., \"USDC\" or EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v)"
                     },
                     "outputMint": {
                         "type": "string", 
-                        "description": "The token address (mint) you want to swap TO (e.g., SOL: So11111111111111111111111111111111111111112)"
+                        "description": "The token you want to swap TO, as a mint address or a symbol (e.g., \"SOL\" or So11111111111111111111111111111111111111112)"
                     },
                     "amount": {
                         "type": "string",
-                        "description": "How much of the input token to swap (in the token's smallest unit - for USDC with 6 decimals, 1000000 = 1 USDC)"
+                        "description": "How much to swap: the input token for ExactIn, the output token for ExactOut, counted in amountUnits. A decimal or an amount with a symbol (e.g. \"1.5\" or \"250 USDC\") is always whole tokens; a plain integer such as \"1\" is rejected unless amountUnits is set."
+                    },
+                    "amountUnits": {
+                        "type": "string",
+                        "enum": ["base", "ui"],
+                        "description": "\"base\" for the token's smallest unit (for USDC with 6 decimals, 1000000 = 1 USDC) or \"ui\" for whole tokens. Required when amount is a plain integer."
                     },
                     "taker": {
                         "type": "string",
-                        "description": "The wallet address that will perform the swap"
+                        "description": "The wallet address that will perform the swap (optional, defaults to the server wallet). Must be the server wallet for execute_swap to sign the order."
                     },
                     "swapMode": {
                         "type": "string",
@@ -106,62 +151,63 @@ impl GetQuoteTool {
                     "inputMint".to_string(),
                     "outputMint".to_string(), 
                     "amount".to_string(),
-                    "taker".to_string()
                 ]),
             },
         }
     }
 
-    pub async fn execute(_config: &Config, args: Value) -> Result<ToolResponse> {
-        let request: QuoteRequest = serde_json::from_value(args)
+    pub async fn execute(ctx: &ToolContext, args: Value) -> Result<ToolResponse> {
+        let mut request: QuoteRequest = serde_json::from_value(args)
             .map_err(|e| JupiterMcpError::InvalidInput(format!("Invalid arguments: {}", e)))?;
 
-        // Validate mint addresses
-        parse_pubkey(&request.input_mint)?;
-        parse_pubkey(&request.output_mint)?;
-
-        // Validate taker address
-        parse_pubkey(&request.taker)?;
+        // Accept symbols such as "USDC" as well as mint addresses
+        request.input_mint = ctx.tokens.resolve_mint(&ctx.jupiter, &request.input_mint).await?;
+        request.output_mint = ctx.tokens.resolve_mint(&ctx.jupiter, &request.output_mint).await?;
 
-        // Validate amount
-        request
-            .amount
-            .parse::<u64>()
-            .map_err(|e| JupiterMcpError::InvalidInput(format!("Invalid amount: {}", e)))?;
+        // Validate taker address, defaulting to the server wallet
+        let taker = match request.taker {
+            Some(taker) => taker,
+            None => load_wallet(&ctx.config)?.pubkey().to_string(),
+        };
+        parse_pubkey(&taker)?;
 
         let slippage_bps = request.slippage_bps.unwrap_or(50);
         let swap_mode = request.swap_mode.unwrap_or_else(|| "ExactIn".to_string());
 
+        // The amount is denominated in the token whose side is fixed
+        let amount_mint = if swap_mode == "ExactOut" { &request.output_mint } else { &request.input_mint };
+        request.amount = resolve_amount(ctx, &request.amount, request.amount_units, amount_mint).await?.to_string();
+
         // Build query parameters
         let mut params = HashMap::new();
         params.insert("inputMint", request.input_mint.clone());
         params.insert("outputMint", request.output_mint.clone());
         params.insert("amount", request.amount.clone());
-        params.insert("taker", request.taker.clone());
         params.insert("swapMode", swap_mode);
         params.insert("slippageBps", slippage_bps.to_string());
 
-        // Make request to Jupiter Ultra API
-        let client = reqwest::Client::new();
-        let response = client
-            .get("https://ultra-api.jup.ag/order")
-            .query(&params)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(JupiterMcpError::JupiterApi(format!(
-                "Jupiter API error {}: {}",
-                status, error_text
-            )));
-        }
+        // Ultra returns an order with an unsigned transaction for the taker,
+        // v6 returns a bare quote that is turned into a transaction by /swap
+        let quote = match ctx.jupiter.backend() {
+            JupiterBackend::Ultra => {
+                params.insert("taker", taker);
+                ctx.jupiter.order(&params).await?
+            }
+            JupiterBackend::SwapV6 => ctx.jupiter.quote(&params).await?,
+        };
+
+        // Keep the quote server-side so it can be read back as jupiter://quote/{id}
+        let quote_id = ctx.quotes.insert(quote.clone());
 
-        let quote: QuoteResponse = response.json().await?;
+        // Mint details are only needed for display, so a failed lookup falls back to raw amounts
+        let (input_info, output_info) = tokio::join!(
+            mint_info(ctx, &quote.input_mint),
+            mint_info(ctx, &quote.output_mint),
+        );
+        let input_label = ctx.tokens.label(&quote.input_mint);
+        let output_label = ctx.tokens.label(&quote.output_mint);
+        let input_amount = AmountView::new(&quote.in_amount, input_info.as_ref().map(|info| info.decimals), input_label.clone());
+        let output_amount = AmountView::new(&quote.out_amount, output_info.as_ref().map(|info| info.decimals), output_label.clone());
 
         // Format route information
         let route_labels: Vec<String> = quote
@@ -170,22 +216,260 @@ impl GetQuoteTool {
             .map(|r| r.swap_info.label.clone())
             .collect();
 
-        let response_text = format!(
+        let mut response_text = format!(
             "✅ Quote received for your swap:\n\n\
-            📥 You will send: {} tokens\n\
-            📤 You will receive: {} tokens\n\
+            🆔 Quote ID: {}\n\
+            📥 You will send: {}\n\
+            📤 You will receive: {}\n\
             💹 Price impact: {}%\n\
             ⚡ Slippage tolerance: {} bps ({}%)\n\
             🛣️  Best route: {}\n\n\
-            This quote is ready to use for executing the swap.",
-            quote.in_amount,
-            quote.out_amount,
+            Pass the quote ID to execute_swap to perform this swap.",
+            quote_id,
+            input_amount,
+            output_amount,
             quote.price_impact_pct,
             quote.slippage_bps,
             (quote.slippage_bps as f64) / 100.0,
             route_labels.join(" → ")
         );
 
-        Ok(ToolResponse::text(response_text))
+        let notes = [
+            input_info.as_ref().map(|info| token_2022_notes(info, &input_label, None)),
+            output_info.as_ref().map(|info| token_2022_notes(info, &output_label, quote.out_amount.parse().ok())),
+        ];
+        for note in notes.into_iter().flatten().flatten() {
+            response_text.push_str(&format!("\n\n⚠️  {}", note));
+        }
+
+        if ctx.jupiter.backend() == JupiterBackend::Ultra && quote.transaction().is_none() {
+            response_text.push_str(&format!(
+                "\n\n⚠️  Jupiter did not attach a transaction to this order, so it cannot be executed: {}",
+                quote.error_message().unwrap_or("no reason given")
+            ));
+        }
+
+        let structured = json!({
+            "quoteId": quote_id,
+            "quoteResponse": serde_json::to_value(&quote)?,
+            "inputAmount": input_amount.to_json(),
+            "outputAmount": output_amount.to_json(),
+            "inputToken": input_info.as_ref().map(MintInfo::to_json),
+            "outputToken": output_info.as_ref().map(MintInfo::to_json),
+            "summary": response_text,
+        });
+
+        Ok(ToolResponse::text(response_text).with_structured(structured))
+    }
+}
+
+/// Convert a requested amount into base units of `mint`. Whole tokens are
+/// scaled by the mint's on-chain decimals.
+async fn resolve_amount(ctx: &ToolContext, amount: &str, units: Option<AmountUnits>, mint: &str) -> Result<u64> {
+    let (number, unit) = split_amount(amount)?;
+
+    if amount_units(number, unit, units)? == AmountUnits::Base {
+        return parse_amount(number);
+    }
+    if let Some(unit) = unit {
+        let unit_mint = ctx.tokens.resolve_mint(&ctx.jupiter, unit).await?;
+        if unit_mint != mint {
+            return Err(JupiterMcpError::InvalidInput(format!(
+                "Amount is given in {} but this swap is sized in {}",
+                unit,
+                ctx.tokens.label(mint)
+            )));
+        }
+    }
+
+    let decimals = get_mint_info(&ctx.rpc, &parse_pubkey(mint)?).await?.decimals;
+    TokenAmount::parse(number, decimals)?.to_u64()
+}
+
+/// Units of an amount. A decimal point or a symbol means whole tokens; a
+/// plain integer could be either, so it needs `amountUnits` rather than a
+/// guess that may be off by a factor of 10^decimals.
+fn amount_units(number: &str, symbol: Option<&str>, units: Option<AmountUnits>) -> Result<AmountUnits> {
+    let whole_tokens = symbol.is_some() || number.contains('.');
+    match (units, whole_tokens) {
+        (Some(AmountUnits::Base), true) => Err(JupiterMcpError::InvalidInput(format!(
+            "Amount '{}' is in whole tokens, but amountUnits is \"base\"; give an integer number of base units",
+            number
+        ))),
+        (Some(units), _) => Ok(units),
+        (None, true) => Ok(AmountUnits::Ui),
+        (None, false) => Err(JupiterMcpError::InvalidInput(format!(
+            "Amount '{0}' is ambiguous: set amountUnits to \"base\" for {0} base units or \"ui\" for {0} whole tokens",
+            number
+        ))),
+    }
+}
+
+/// Split "250 USDC" into the number and an optional unit
+fn split_amount(amount: &str) -> Result<(&str, Option<&str>)> {
+    let mut parts = amount.split_whitespace();
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(number), unit, None) => Ok((number, unit)),
+        _ => Err(JupiterMcpError::InvalidInput(format!(
+            "Invalid amount '{}': expected a number optionally followed by a token symbol",
+            amount
+        ))),
+    }
+}
+
+async fn mint_info(ctx: &ToolContext, mint: &str) -> Option<MintInfo> {
+    let mint = parse_pubkey(mint).ok()?;
+    get_mint_info(&ctx.rpc, &mint).await.ok()
+}
+
+/// Warnings for Token-2022 extensions; `received` is the quoted output,
+/// used to show what is left after a transfer fee
+fn token_2022_notes(info: &MintInfo, label: &str, received: Option<u64>) -> Option<String> {
+    if info.extensions.is_empty() {
+        return None;
+    }
+
+    let mut note = format!("{} is a Token-2022 mint: {}.", label, info.describe_extensions().join("; "));
+    if let Some((received, fee)) = received.and_then(|amount| Some((amount, info.transfer_fee(amount)?))) {
+        note.push_str(&format!(
+            " After the transfer fee of {} you would receive about {} {}.",
+            TokenAmount::new(fee, info.decimals),
+            TokenAmount::new(received.saturating_sub(fee), info.decimals),
+            label
+        ));
+    }
+    Some(note)
+}
+
+/// A quoted amount shown both in base units and in whole tokens
+struct AmountView {
+    raw: String,
+    amount: Option<TokenAmount>,
+    label: String,
+}
+
+impl AmountView {
+    fn new(raw: &str, decimals: Option<u8>, label: String) -> Self {
+        let amount = decimals.and_then(|decimals| Some(TokenAmount::new(raw.parse::<u128>().ok()?, decimals)));
+        Self {
+            raw: raw.to_string(),
+            amount,
+            label,
+        }
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "raw": self.raw,
+            "ui": self.amount.map(|amount| amount.to_plain_string()),
+            "decimals": self.amount.map(|amount| amount.decimals()),
+        })
+    }
+}
+
+impl std::fmt::Display for AmountView {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.amount {
+            Some(amount) => write!(f, "{} {} ({} base units)", amount, self.label, self.raw),
+            None => write!(f, "{} base units of {}", self.raw, self.label),
+        }
+    }
+}
+
+/// Quote fixtures shared by tests across the crate
+#[cfg(test)]
+pub(crate) mod test_quotes {
+    use super::QuoteResponse;
+    use serde_json::{json, Value};
+
+    pub const SOL: &str = "So11111111111111111111111111111111111111112";
+    pub const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+    pub const JUP: &str = "JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN";
+
+    /// ExactIn quote of 1 SOL for 100 USDC, at least 99 USDC, through one
+    /// Whirlpool pool, as Jupiter returns it
+    pub fn sample_quote_json() -> Value {
+        json!({
+            "inputMint": SOL,
+            "outputMint": USDC,
+            "inAmount": "1000000000",
+            "outAmount": "100000000",
+            "otherAmountThreshold": "99000000",
+            "swapMode": "ExactIn",
+            "slippageBps": 100,
+            "priceImpactPct": "0.12",
+            "platformFee": null,
+            "contextSlot": 123456,
+            "routePlan": [{
+                "swapInfo": {
+                    "ammKey": "HcoJqG325TTifs6jyWvRJ9ET4pDu12Xrt2EQKZGFmuKX",
+                    "label": "Whirlpool",
+                    "inputMint": SOL,
+                    "outputMint": USDC,
+                    "inAmount": "1000000000",
+                    "outAmount": "100000000",
+                    "feeAmount": "100",
+                    "feeMint": SOL,
+                    "poolVersion": 2
+                },
+                "percent": 100,
+                "bps": 10000
+            }]
+        })
+    }
+
+    /// `sample_quote_json` parsed; change its fields with struct update
+    /// syntax, e.g. `QuoteResponse { slippage_bps: 50, ..sample_quote() }`
+    pub fn sample_quote() -> QuoteResponse {
+        serde_json::from_value(sample_quote_json()).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::test_quotes::sample_quote_json;
+
+    #[test]
+    fn test_split_amount() {
+        assert_eq!(split_amount("1.5").unwrap(), ("1.5", None));
+        assert_eq!(split_amount(" 250  USDC ").unwrap(), ("250", Some("USDC")));
+        assert!(split_amount("").is_err());
+        assert!(split_amount("1 SOL extra").is_err());
+    }
+
+    #[test]
+    fn test_amount_units() {
+        assert_eq!(amount_units("1", None, Some(AmountUnits::Base)).unwrap(), AmountUnits::Base);
+        assert_eq!(amount_units("1", None, Some(AmountUnits::Ui)).unwrap(), AmountUnits::Ui);
+        assert_eq!(amount_units("1.0", None, None).unwrap(), AmountUnits::Ui);
+        assert_eq!(amount_units("1", Some("SOL"), None).unwrap(), AmountUnits::Ui);
+
+        // A bare integer is never guessed at
+        assert!(amount_units("1", None, None).unwrap_err().to_string().contains("ambiguous"));
+        assert!(amount_units("1.5", None, Some(AmountUnits::Base)).is_err());
+        assert!(amount_units("1", Some("SOL"), Some(AmountUnits::Base)).is_err());
+    }
+
+    #[test]
+    fn test_amount_view_shows_raw_and_ui() {
+        let view = AmountView::new("1500000000", Some(9), "SOL".to_string());
+        assert_eq!(view.to_string(), "1.5 SOL (1500000000 base units)");
+        assert_eq!(view.to_json()["ui"], "1.5");
+
+        let view = AmountView::new("12345000000", Some(6), "USDC".to_string());
+        assert_eq!(view.to_string(), "12,345 USDC (12345000000 base units)");
+
+        let view = AmountView::new("42", None, "mint".to_string());
+        assert_eq!(view.to_string(), "42 base units of mint");
+    }
+
+    #[test]
+    fn test_quote_response_preserves_unknown_fields() {
+        let raw = sample_quote_json();
+        let quote: QuoteResponse = serde_json::from_value(raw.clone()).unwrap();
+
+        assert_eq!(quote.extra["contextSlot"], json!(123456));
+        assert_eq!(serde_json::to_value(&quote).unwrap(), raw);
     }
 }
@@ -0,0 +1,135 @@
+use crate::{JupiterMcpError, Result};
+use crate::journal::{format_timestamp, JournalEntry, JournalFilter, SwapStatus};
+use crate::mcp::{Tool, ToolInputSchema, ToolResponse};
+use crate::tools::ToolContext;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Entries returned when the caller does not set a limit
+const DEFAULT_LIMIT: usize = 20;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SwapHistoryRequest {
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+    pub mint: Option<String>,
+    pub status: Option<String>,
+    pub limit: Option<usize>,
+}
+
+pub struct GetSwapHistoryTool;
+
+impl GetSwapHistoryTool {
+    pub fn definition() -> Tool {
+        Tool {
+            name: "get_swap_history".to_string(),
+            description: "List swaps recorded in the server's swap journal, newest first, with their quote, policy decision, signature, status and realized amounts".to_string(),
+            input_schema: ToolInputSchema {
+                schema_type: "object".to_string(),
+                properties: json!({
+                    "since": {
+                        "type": "integer",
+                        "description": "Only swaps at or after this time, in unix seconds (optional)"
+                    },
+                    "until": {
+                        "type": "integer",
+                        "description": "Only swaps at or before this time, in unix seconds (optional)"
+                    },
+                    "mint": {
+                        "type": "string",
+                        "description": "Only swaps with this token on either side, as a mint address or symbol (optional)"
+                    },
+                    "status": {
+                        "type": "string",
+                        "enum": ["confirmed", "failed", "expired", "rejected", "error"],
+                        "description": "Only swaps that ended this way (optional)"
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "description": "Maximum number of swaps to return (default: 20)"
+                    }
+                }),
+                required: None,
+            },
+        }
+    }
+
+    pub async fn execute(ctx: &ToolContext, args: Value) -> Result<ToolResponse> {
+        let request: SwapHistoryRequest = serde_json::from_value(args)
+            .map_err(|e| JupiterMcpError::InvalidInput(format!("Invalid arguments: {}", e)))?;
+
+        if ctx.journal.path().is_none() {
+            return Ok(ToolResponse::error(
+                "The swap journal is disabled; set SWAP_JOURNAL_PATH to record swaps".to_string()
+            ));
+        }
+
+        let mint = match &request.mint {
+            Some(token) => Some(ctx.tokens.resolve_mint(&ctx.jupiter, token).await?),
+            None => None,
+        };
+        let status = request.status.as_deref()
+            .map(str::parse::<SwapStatus>)
+            .transpose()
+            .map_err(JupiterMcpError::InvalidInput)?;
+        let filter = JournalFilter { since: request.since, until: request.until, mint, status };
+
+        let mut entries = ctx.journal.query(&filter)?;
+        let total = entries.len();
+        entries.reverse();
+        entries.truncate(request.limit.unwrap_or(DEFAULT_LIMIT).max(1));
+
+        let mut response_text = if entries.is_empty() {
+            "No swaps match.".to_string()
+        } else {
+            format!("Showing {} of {} matching swaps, newest first:\n", entries.len(), total)
+        };
+        for entry in &entries {
+            response_text.push_str(&format!("\n{}", describe_entry(ctx, entry)));
+        }
+
+        Ok(ToolResponse::text(response_text).with_structured(json!({
+            "total": total,
+            "entries": entries,
+        })))
+    }
+}
+
+/// One line per swap, plus its signature or the reason it did not go through
+fn describe_entry(ctx: &ToolContext, entry: &JournalEntry) -> String {
+    let input = ctx.tokens.label(&entry.quote.input_mint);
+    let output = ctx.tokens.label(&entry.quote.output_mint);
+    let (input_amount, output_amount) = match &entry.realized {
+        Some(realized) => (realized.input_spent.to_string(), realized.output_received.to_string()),
+        None => (entry.quote.in_amount.clone(), entry.quote.out_amount.clone()),
+    };
+
+    let mut line = format!(
+        "{} [{:?}] {} {} → {} {} (base units, {}), quote {}",
+        format_timestamp(entry.timestamp),
+        entry.status,
+        input_amount,
+        input,
+        output_amount,
+        output,
+        if entry.realized.is_some() { "realized" } else { "quoted" },
+        entry.quote_id,
+    );
+    if let Some(signature) = &entry.signature {
+        line.push_str(&format!("\n  Signature: {}", signature));
+    }
+    if let Some(realized) = &entry.realized {
+        line.push_str(&format!("\n  Slippage vs quote: {:.1} bps", realized.slippage_bps));
+    }
+    if let Some(rule) = &entry.policy.rule {
+        line.push_str(&format!("\n  Policy rule: {}", rule));
+    }
+    if let Some(confirmation) = &entry.policy.confirmation {
+        line.push_str(&format!("\n  Confirmation: {}", confirmation));
+    }
+    if let Some(error) = &entry.error {
+        line.push_str(&format!("\n  Error: {}", error));
+    }
+    line
+}
@@ -0,0 +1,210 @@
+use crate::{JupiterMcpError, Result};
+use crate::landing::fetch_transaction;
+use crate::mcp::{Tool, ToolInputSchema, ToolResponse};
+use crate::settlement::WalletDeltas;
+use crate::tools::execute_swap::describe_fee;
+use crate::tools::ToolContext;
+use crate::utils::{get_explorer_url, load_wallet, parse_pubkey, TokenAmount, SOL_DECIMALS};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{pubkey::Pubkey, signature::{Signature, Signer}};
+use solana_transaction_status::TransactionConfirmationStatus;
+use std::str::FromStr;
+use tracing::warn;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TransactionStatusRequest {
+    pub signature: String,
+    #[serde(rename = "walletAddress")]
+    pub wallet_address: Option<String>,
+}
+
+/// What the cluster knows about a transaction
+#[derive(Debug, Serialize)]
+pub struct TransactionReport {
+    pub signature: String,
+    pub found: bool,
+    #[serde(rename = "confirmationStatus")]
+    pub confirmation_status: Option<TransactionConfirmationStatus>,
+    pub slot: Option<u64>,
+    #[serde(rename = "blockTime")]
+    pub block_time: Option<i64>,
+    pub fee: Option<u64>,
+    pub error: Option<String>,
+    /// Balance changes of the wallet, once the transaction is confirmed
+    #[serde(rename = "balanceChanges")]
+    pub balance_changes: Option<WalletDeltas>,
+}
+
+pub struct GetTransactionStatusTool;
+
+impl GetTransactionStatusTool {
+    pub fn definition() -> Tool {
+        Tool {
+            name: "get_transaction_status".to_string(),
+            description: "Look up a transaction by signature: confirmation status, slot, block time, fee, error and the wallet's token balance changes".to_string(),
+            input_schema: ToolInputSchema {
+                schema_type: "object".to_string(),
+                properties: json!({
+                    "signature": {
+                        "type": "string",
+                        "description": "Transaction signature, e.g. one returned by execute_swap"
+                    },
+                    "walletAddress": {
+                        "type": "string",
+                        "description": "Wallet whose balance changes to report (optional, defaults to the server wallet)"
+                    }
+                }),
+                required: Some(vec!["signature".to_string()]),
+            },
+        }
+    }
+
+    pub async fn execute(ctx: &ToolContext, args: Value) -> Result<ToolResponse> {
+        let request: TransactionStatusRequest = serde_json::from_value(args)
+            .map_err(|e| JupiterMcpError::InvalidInput(format!("Invalid arguments: {}", e)))?;
+
+        let signature = Signature::from_str(request.signature.trim())
+            .map_err(|e| JupiterMcpError::InvalidInput(format!("Invalid signature '{}': {}", request.signature, e)))?;
+        let wallet = match &request.wallet_address {
+            Some(address) => parse_pubkey(address)?,
+            None => load_wallet(&ctx.config)?.pubkey(),
+        };
+
+        let report = lookup_transaction(&ctx.rpc, &signature, &wallet).await?;
+
+        let mut response_text = format!("Transaction {}\n", signature);
+        if !report.found {
+            response_text.push_str(
+                "Status: not found. It has not landed yet, or its blockhash expired before it could."
+            );
+            return Ok(ToolResponse::text(response_text).with_structured(serde_json::to_value(&report)?));
+        }
+
+        let status = match (&report.error, &report.confirmation_status) {
+            (Some(error), _) => format!("failed: {}", error),
+            (None, Some(commitment)) => format!("{:?}", commitment),
+            (None, None) => "unknown".to_string(),
+        };
+        response_text.push_str(&format!(
+            "Status: {}\n\
+            Slot: {}\n\
+            Block time: {}\n\
+            Fee: {}\n",
+            status,
+            report.slot.map(|slot| slot.to_string()).unwrap_or_else(|| "unknown".to_string()),
+            report.block_time.map(|time| format!("{} (unix)", time)).unwrap_or_else(|| "unknown".to_string()),
+            describe_fee(report.fee),
+        ));
+
+        match &report.balance_changes {
+            Some(deltas) => {
+                response_text.push_str(&format!("Balance changes for {}:\n", wallet));
+                if deltas.sol_change != 0 {
+                    response_text.push_str(&format!("  {}\n", format_change(deltas.sol_change, SOL_DECIMALS, "SOL")));
+                }
+                for delta in &deltas.tokens {
+                    let label = ctx.tokens.label(&delta.mint);
+                    response_text.push_str(&format!("  {}\n", format_change(delta.change, delta.decimals, &label)));
+                }
+                if deltas.sol_change == 0 && deltas.tokens.is_empty() {
+                    response_text.push_str("  none\n");
+                }
+            }
+            None => response_text.push_str("Balance changes: not available until the transaction is confirmed\n"),
+        }
+        response_text.push_str(&format!("Explorer: {}", get_explorer_url(&signature, &ctx.config)));
+
+        Ok(ToolResponse::text(response_text).with_structured(serde_json::to_value(&report)?))
+    }
+}
+
+/// Look a signature up in the status cache and the ledger history
+pub async fn lookup_transaction(
+    rpc: &RpcClient,
+    signature: &Signature,
+    wallet: &Pubkey,
+) -> Result<TransactionReport> {
+    let status = rpc.get_signature_statuses_with_history(&[*signature]).await?
+        .value
+        .into_iter()
+        .next()
+        .flatten();
+
+    let mut report = TransactionReport {
+        signature: signature.to_string(),
+        found: status.is_some(),
+        confirmation_status: None,
+        slot: None,
+        block_time: None,
+        fee: None,
+        error: None,
+        balance_changes: None,
+    };
+
+    let Some(status) = status else {
+        return Ok(report);
+    };
+    report.confirmation_status = Some(status.confirmation_status());
+    report.slot = Some(status.slot);
+    report.error = status.err.as_ref().map(ToString::to_string);
+
+    // getTransaction only serves confirmed transactions
+    if report.confirmation_status == Some(TransactionConfirmationStatus::Processed) {
+        return Ok(report);
+    }
+    match fetch_transaction(rpc, signature).await {
+        Ok(transaction) => {
+            report.block_time = transaction.block_time;
+            report.fee = transaction.transaction.meta.as_ref().map(|meta| meta.fee);
+            report.balance_changes = WalletDeltas::from_transaction(&transaction, wallet);
+        }
+        Err(e) => warn!("Could not fetch transaction {}: {}", signature, e),
+    }
+
+    Ok(report)
+}
+
+/// Signed whole-token amount, e.g. "-1.5 SOL"
+fn format_change(change: i128, decimals: u8, label: &str) -> String {
+    let sign = if change < 0 { "-" } else { "+" };
+    format!("{}{} {}", sign, TokenAmount::new(change.unsigned_abs(), decimals), label)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fee payer of the mock node's canned transaction
+    const MOCK_PAYER: &str = "C6eBmAXKg6JhJWkajGa5YRGUfG4YKXwbxF5Ufv7PtExZ";
+
+    #[tokio::test]
+    async fn test_lookup_confirmed_transaction() {
+        let rpc = RpcClient::new_mock("succeeds".to_string());
+        let report = lookup_transaction(&rpc, &Signature::default(), &MOCK_PAYER.parse().unwrap()).await.unwrap();
+
+        assert!(report.found);
+        assert_eq!(report.confirmation_status, Some(TransactionConfirmationStatus::Finalized));
+        assert_eq!(report.slot, Some(1));
+        assert_eq!(report.block_time, Some(1628633791));
+        assert_eq!(report.fee, Some(0));
+        assert_eq!(report.error, None);
+        assert_eq!(report.balance_changes, Some(WalletDeltas::default()));
+    }
+
+    #[tokio::test]
+    async fn test_lookup_unknown_transaction() {
+        let rpc = RpcClient::new_mock("sig_not_found".to_string());
+        let report = lookup_transaction(&rpc, &Signature::default(), &Pubkey::new_unique()).await.unwrap();
+
+        assert!(!report.found);
+        assert_eq!(serde_json::to_value(&report).unwrap()["confirmationStatus"], Value::Null);
+    }
+
+    #[test]
+    fn test_format_change() {
+        assert_eq!(format_change(-1_500_000_000, 9, "SOL"), "-1.5 SOL");
+        assert_eq!(format_change(99_500_000, 6, "USDC"), "+99.5 USDC");
+    }
+}
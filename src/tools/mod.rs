@@ -1,7 +1,34 @@
 pub mod get_quote;
 pub mod execute_swap;
 pub mod get_balance;
+pub mod get_portfolio;
+pub mod get_swap_history;
+pub mod get_transaction_status;
 
 pub use get_quote::GetQuoteTool;
 pub use execute_swap::ExecuteSwapTool;
-pub use get_balance::GetBalanceTool;
\ No newline at end of file
+pub use get_balance::GetBalanceTool;
+pub use get_portfolio::GetPortfolioTool;
+pub use get_swap_history::GetSwapHistoryTool;
+pub use get_transaction_status::GetTransactionStatusTool;
+
+use crate::journal::SwapJournal;
+use crate::jupiter::JupiterClient;
+use crate::policy::PolicyEngine;
+use crate::quotes::QuoteStore;
+use crate::tokens::TokenRegistry;
+use crate::Config;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use std::sync::Arc;
+
+/// Shared handles every tool call runs against
+#[derive(Clone)]
+pub struct ToolContext {
+    pub config: Config,
+    pub jupiter: JupiterClient,
+    pub rpc: Arc<RpcClient>,
+    pub quotes: Arc<QuoteStore>,
+    pub policy: Arc<PolicyEngine>,
+    pub tokens: Arc<TokenRegistry>,
+    pub journal: Arc<SwapJournal>,
+}
\ No newline at end of file
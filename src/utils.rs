@@ -1,14 +1,14 @@
 use crate::{Config, JupiterMcpError, Result};
-use solana_client::rpc_client::RpcClient;
+use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::{
     pubkey::Pubkey,
     signature::{Keypair, Signature},
 };
 use std::str::FromStr;
 
-/// Get a configured Solana RPC client
+/// Get a configured nonblocking Solana RPC client
 pub fn get_connection(config: &Config) -> RpcClient {
-    RpcClient::new_with_commitment(&config.rpc_url, config.commitment)
+    RpcClient::new_with_commitment(config.rpc_url.clone(), config.commitment)
 }
 
 /// Load wallet from private key in config
@@ -20,16 +20,19 @@ pub fn load_wallet(config: &Config) -> Result<Keypair> {
     })
 }
 
+/// Decimals of native SOL
+pub const SOL_DECIMALS: u8 = 9;
+
 /// Format lamports as SOL with proper decimal places
 pub fn format_sol(lamports: u64) -> String {
-    format!("{:.9}", lamports as f64 / 1_000_000_000.0)
+    TokenAmount::new(lamports, SOL_DECIMALS).to_fixed_string()
 }
 
 /// Get explorer URL for a transaction signature
 pub fn get_explorer_url(signature: &Signature, config: &Config) -> String {
     let base_url = "https://explorer.solana.com/tx";
     match config.network.cluster_param() {
-        Some(cluster) => format!("{}?cluster={}", base_url, cluster),
+        Some(cluster) => format!("{}/{}?cluster={}", base_url, signature, cluster),
         None => format!("{}/{}", base_url, signature),
     }
 }
@@ -50,8 +53,141 @@ pub fn parse_amount(amount_str: &str) -> Result<u64> {
 
 /// Format token amount with proper decimals
 pub fn format_token_amount(amount: u64, decimals: u8) -> String {
-    let divisor = 10_u64.pow(decimals as u32) as f64;
-    format!("{:.prec$}", amount as f64 / divisor, prec = decimals as usize)
+    TokenAmount::new(amount, decimals).to_fixed_string()
+}
+
+/// Create a directory and its parents, readable only by the current user
+pub fn create_private_dir(dir: &std::path::Path) -> std::io::Result<()> {
+    let mut builder = std::fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(dir)
+}
+
+/// An exact token amount: base units plus the mint's decimals.
+///
+/// `Display` shows whole tokens with thousands separators and without
+/// trailing zeros, e.g. `1,234.5`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TokenAmount {
+    raw: u128,
+    decimals: u8,
+}
+
+impl TokenAmount {
+    pub fn new(raw: impl Into<u128>, decimals: u8) -> Self {
+        Self {
+            raw: raw.into(),
+            decimals,
+        }
+    }
+
+    /// Parse whole tokens such as "1.5" or "1,234.5" using integer math,
+    /// rejecting more fractional digits than `decimals`
+    pub fn parse(amount_str: &str, decimals: u8) -> Result<Self> {
+        let invalid = |reason: &str| JupiterMcpError::InvalidInput(format!("Invalid amount '{}': {}", amount_str, reason));
+
+        let (whole, fraction) = amount_str.split_once('.').unwrap_or((amount_str, ""));
+        let whole = strip_separators(whole).ok_or_else(|| invalid("misplaced thousands separator"))?;
+        if whole.is_empty() && fraction.is_empty() {
+            return Err(invalid("no digits"));
+        }
+        if !whole.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit()) {
+            return Err(invalid("expected a non-negative decimal number"));
+        }
+        if fraction.len() > decimals as usize {
+            return Err(invalid(&format!("the token only supports {} decimal places", decimals)));
+        }
+
+        // Right-pad the fraction to `decimals` digits and read both parts as one integer
+        let digits = format!("{}{:0<width$}", whole, fraction, width = decimals as usize);
+        let digits = digits.trim_start_matches('0');
+        let raw: u128 = if digits.is_empty() {
+            0
+        } else {
+            digits.parse().map_err(|_| invalid("too large for a token amount"))?
+        };
+        Ok(Self::new(raw, decimals))
+    }
+
+    pub fn raw(&self) -> u128 {
+        self.raw
+    }
+
+    pub fn decimals(&self) -> u8 {
+        self.decimals
+    }
+
+    /// Base units as a `u64`, the width of SPL token amounts
+    pub fn to_u64(&self) -> Result<u64> {
+        u64::try_from(self.raw).map_err(|_| JupiterMcpError::InvalidInput(format!(
+            "Amount {} is too large for a token amount", self
+        )))
+    }
+
+    /// Whole tokens without separators or trailing zeros, e.g. `1234.5`
+    pub fn to_plain_string(&self) -> String {
+        let (whole, fraction) = self.split();
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.is_empty() {
+            whole
+        } else {
+            format!("{}.{}", whole, fraction)
+        }
+    }
+
+    /// Whole tokens with every decimal place, e.g. `1.500000000`
+    pub fn to_fixed_string(&self) -> String {
+        match self.split() {
+            (whole, fraction) if fraction.is_empty() => whole,
+            (whole, fraction) => format!("{}.{}", whole, fraction),
+        }
+    }
+
+    fn split(&self) -> (String, String) {
+        let digits = format!("{:0>width$}", self.raw, width = self.decimals as usize + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - self.decimals as usize);
+        (whole.to_string(), fraction.to_string())
+    }
+}
+
+impl std::fmt::Display for TokenAmount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let plain = self.to_plain_string();
+        let (whole, fraction) = plain.split_once('.').map_or((plain.as_str(), None), |(w, f)| (w, Some(f)));
+
+        for (i, digit) in whole.chars().enumerate() {
+            if i > 0 && (whole.len() - i) % 3 == 0 {
+                f.write_str(",")?;
+            }
+            write!(f, "{}", digit)?;
+        }
+        match fraction {
+            Some(fraction) => write!(f, ".{}", fraction),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Remove thousands separators from the whole part, requiring groups of three
+fn strip_separators(whole: &str) -> Option<String> {
+    if !whole.contains(',') {
+        return Some(whole.to_string());
+    }
+    let mut groups = whole.split(',');
+    let first = groups.next()?;
+    if first.is_empty() || first.len() > 3 {
+        return None;
+    }
+    let mut digits = first.to_string();
+    for group in groups {
+        if group.len() != 3 {
+            return None;
+        }
+        digits.push_str(group);
+    }
+    Some(digits)
 }
 
 #[cfg(test)]
@@ -65,6 +201,23 @@ mod tests {
         assert_eq!(format_sol(1), "0.000000001");
     }
     
+    #[test]
+    fn test_explorer_url_includes_signature() {
+        let mut config = crate::server::tests::test_config();
+        let signature = Signature::from([7; 64]);
+
+        assert_eq!(
+            get_explorer_url(&signature, &config),
+            format!("https://explorer.solana.com/tx/{}?cluster=devnet", signature)
+        );
+
+        config.network = crate::config::SolanaNetwork::MainnetBeta;
+        assert_eq!(
+            get_explorer_url(&signature, &config),
+            format!("https://explorer.solana.com/tx/{}", signature)
+        );
+    }
+    
     #[test]
     fn test_parse_amount() {
         assert_eq!(parse_amount("1000").unwrap(), 1000);
@@ -77,4 +230,57 @@ mod tests {
         assert_eq!(format_token_amount(1_000_000, 6), "1.000000");
         assert_eq!(format_token_amount(500_000, 6), "0.500000");
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_token_amount_parse() {
+        let parse = |s: &str, decimals: u8| TokenAmount::parse(s, decimals).map(|amount| amount.raw());
+        assert_eq!(parse("1.5", 9).unwrap(), 1_500_000_000);
+        assert_eq!(parse("250", 6).unwrap(), 250_000_000);
+        assert_eq!(parse(".25", 2).unwrap(), 25);
+        assert_eq!(parse("0.000001", 6).unwrap(), 1);
+        assert_eq!(parse("1,234,567.5", 1).unwrap(), 12_345_675);
+        assert!(parse("0.0000001", 6).is_err());
+        assert!(parse("1,23.5", 1).is_err());
+        assert!(parse("1.2.3", 6).is_err());
+        assert!(parse("-1", 6).is_err());
+        assert!(parse(".", 6).is_err());
+        assert!(TokenAmount::parse("18446744073709.551616", 6).unwrap().to_u64().is_err());
+    }
+    
+    #[test]
+    fn test_token_amount_display() {
+        assert_eq!(TokenAmount::new(1_500_000_000u64, 9).to_string(), "1.5");
+        assert_eq!(TokenAmount::new(1_234_567_000_000u64, 6).to_string(), "1,234,567");
+        assert_eq!(TokenAmount::new(1u64, 6).to_string(), "0.000001");
+        assert_eq!(TokenAmount::new(u64::MAX, 9).to_string(), "18,446,744,073.709551615");
+        assert_eq!(TokenAmount::new(u64::MAX, 9).to_plain_string(), "18446744073.709551615");
+        // Far beyond f64's 2^53 integer precision
+        assert_eq!(TokenAmount::new(9_007_199_254_740_993u64, 0).to_plain_string(), "9007199254740993");
+    }
+    
+    proptest::proptest! {
+        #[test]
+        fn prop_display_round_trips(raw: u128, decimals in 0u8..=40) {
+            let amount = TokenAmount::new(raw, decimals);
+            proptest::prop_assert_eq!(TokenAmount::parse(&amount.to_string(), decimals).unwrap(), amount);
+            proptest::prop_assert_eq!(TokenAmount::parse(&amount.to_plain_string(), decimals).unwrap(), amount);
+            proptest::prop_assert_eq!(TokenAmount::parse(&amount.to_fixed_string(), decimals).unwrap(), amount);
+        }
+        
+        #[test]
+        fn prop_fixed_string_matches_integer_division(raw: u64, decimals in 0u8..=19) {
+            let scale = 10u128.pow(decimals as u32);
+            let expected = match decimals {
+                0 => raw.to_string(),
+                _ => format!("{}.{:0width$}", raw as u128 / scale, raw as u128 % scale, width = decimals as usize),
+            };
+            proptest::prop_assert_eq!(format_token_amount(raw, decimals), expected);
+        }
+        
+        #[test]
+        fn prop_separators_only_group_whole_digits(raw: u128, decimals in 0u8..=12) {
+            let amount = TokenAmount::new(raw, decimals);
+            proptest::prop_assert_eq!(amount.to_string().replace(',', ""), amount.to_plain_string());
+        }
+    }
+}